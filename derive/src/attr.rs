@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use alloc::string::{String, ToString};
 use syn::{
     parse::{Parse, ParseBuffer},
     punctuated::Punctuated,
     spanned::Spanned,
-    LitStr, Token,
+    Attribute, LitStr, Token,
 };
 
 const SCALE_INFO: &str = "scale_info";
@@ -27,6 +28,12 @@ mod keywords {
     syn::custom_keyword!(skip_type_params);
     syn::custom_keyword!(capture_docs);
     syn::custom_keyword!(replace_segment);
+    syn::custom_keyword!(transparent);
+    syn::custom_keyword!(identity);
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(rename_all);
+    syn::custom_keyword!(with);
+    syn::custom_keyword!(skip);
 }
 
 /// Parsed and validated set of `#[scale_info(...)]` attributes for an item.
@@ -36,6 +43,9 @@ pub struct Attributes {
     capture_docs: Option<CaptureDocsAttr>,
     crate_path: Option<CratePathAttr>,
     replace_segments: Vec<ReplaceSegment>,
+    transparent: Option<TransparentAttr>,
+    rename: Option<RenameAttr>,
+    rename_all: Option<RenameAllAttr>,
 }
 
 impl Attributes {
@@ -46,6 +56,12 @@ impl Attributes {
         let mut capture_docs = None;
         let mut crate_path = None;
         let mut replace_segments = Vec::new();
+        let mut transparent = None;
+        let mut rename = None;
+        let mut rename_all = None;
+        // The first attribute which `transparent` would ignore, as the type is described by
+        // its field instead.
+        let mut ignored_by_transparent = None;
 
         let attributes_parser = |input: &ParseBuffer| {
             let attrs = input.parse_terminated(ScaleInfoAttr::parse, Token![,])?;
@@ -77,6 +93,7 @@ impl Attributes {
                                 "Duplicate `skip_type_params` attributes",
                             ));
                         }
+                        ignored_by_transparent.get_or_insert((attr.span(), "skip_type_params"));
                         skip_type_params = Some(parsed_skip_type_params);
                     }
                     ScaleInfoAttr::CaptureDocs(parsed_capture_docs) => {
@@ -86,6 +103,7 @@ impl Attributes {
                                 "Duplicate `capture_docs` attributes",
                             ));
                         }
+                        ignored_by_transparent.get_or_insert((attr.span(), "capture_docs"));
                         capture_docs = Some(parsed_capture_docs);
                     }
                     ScaleInfoAttr::CratePath(parsed_crate_path) => {
@@ -99,8 +117,63 @@ impl Attributes {
                         crate_path = Some(parsed_crate_path);
                     }
                     ScaleInfoAttr::ReplaceSegment(replace_segment) => {
+                        ignored_by_transparent.get_or_insert((attr.span(), "replace_segment"));
                         replace_segments.push(replace_segment);
                     }
+                    ScaleInfoAttr::Transparent(parsed_transparent) => {
+                        if transparent.is_some() {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `transparent` attributes",
+                            ));
+                        }
+                        if !has_single_field(item) {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "`transparent` requires a struct with exactly one non-skipped field",
+                            ));
+                        }
+                        transparent = Some(parsed_transparent);
+                    }
+                    ScaleInfoAttr::Rename(parsed_rename) => {
+                        if rename.is_some() {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `rename` attributes",
+                            ));
+                        }
+                        ignored_by_transparent.get_or_insert((attr.span(), "rename"));
+                        rename = Some(parsed_rename);
+                    }
+                    ScaleInfoAttr::RenameAll(parsed_rename_all) => {
+                        if rename_all.is_some() {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `rename_all` attributes",
+                            ));
+                        }
+                        ignored_by_transparent.get_or_insert((attr.span(), "rename_all"));
+                        rename_all = Some(parsed_rename_all);
+                    }
+                }
+            }
+        }
+
+        if transparent.is_some() {
+            if let Some((span, name)) = ignored_by_transparent {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`{name}` is not supported with `transparent`"),
+                ));
+            }
+            if let syn::Data::Struct(ref data_struct) = item.data {
+                for field in &data_struct.fields {
+                    if let Some(rename) = FieldAttributes::from_attrs(&field.attrs)?.rename() {
+                        return Err(syn::Error::new(
+                            rename.name().span(),
+                            "`rename` is not supported for the field of a `transparent` struct",
+                        ));
+                    }
                 }
             }
         }
@@ -132,6 +205,9 @@ impl Attributes {
             capture_docs,
             crate_path,
             replace_segments,
+            transparent,
+            rename,
+            rename_all,
         })
     }
 
@@ -163,6 +239,123 @@ impl Attributes {
     pub fn replace_segments(&self) -> impl Iterator<Item = &ReplaceSegment> {
         self.replace_segments.iter()
     }
+
+    /// Get the `#[scale_info(transparent)]` attribute, if present.
+    pub fn transparent(&self) -> Option<&TransparentAttr> {
+        self.transparent.as_ref()
+    }
+
+    /// Get the `#[scale_info(rename = "..")]` attribute, if present.
+    pub fn rename(&self) -> Option<&RenameAttr> {
+        self.rename.as_ref()
+    }
+
+    /// Get the `#[scale_info(rename_all = "..")]` attribute, if present.
+    pub fn rename_all(&self) -> Option<&RenameAllAttr> {
+        self.rename_all.as_ref()
+    }
+}
+
+/// Parsed and validated set of `#[scale_info(...)]` attributes for a field or a variant.
+#[derive(Default)]
+pub struct FieldAttributes {
+    with: Option<WithAttr>,
+    rename: Option<RenameAttr>,
+    skip: bool,
+}
+
+impl FieldAttributes {
+    /// Extract out `#[scale_info(...)]` attributes from a variant, which can't be described by
+    /// another type.
+    pub fn from_variant(variant: &syn::Variant) -> syn::Result<Self> {
+        let attrs = Self::from_attrs(&variant.attrs)?;
+        if let Some(ref with) = attrs.with {
+            return Err(syn::Error::new(
+                with.span,
+                "`with` is only accepted for fields",
+            ));
+        }
+        Ok(attrs)
+    }
+
+    /// Extract out `#[scale_info(...)]` attributes from the attributes of a field.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field_attrs = Self::default();
+
+        for attr in attrs {
+            if !attr.path().is_ident(SCALE_INFO) {
+                continue;
+            }
+            let scale_info_attrs = attr.parse_args_with(|input: &ParseBuffer| {
+                input.parse_terminated(FieldAttr::parse, Token![,])
+            })?;
+
+            for scale_info_attr in scale_info_attrs {
+                // check for duplicates
+                match scale_info_attr {
+                    FieldAttr::With(parsed_with) => {
+                        if field_attrs.with.is_some() {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `with` attributes",
+                            ));
+                        }
+                        field_attrs.with = Some(parsed_with);
+                    }
+                    FieldAttr::Rename(parsed_rename) => {
+                        if field_attrs.rename.is_some() {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `rename` attributes",
+                            ));
+                        }
+                        field_attrs.rename = Some(parsed_rename);
+                    }
+                    FieldAttr::Skip => {
+                        if field_attrs.skip {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                "Duplicate `skip` attributes",
+                            ));
+                        }
+                        field_attrs.skip = true;
+                    }
+                }
+            }
+        }
+
+        Ok(field_attrs)
+    }
+
+    /// Get the type given by `#[scale_info(with = $ty)]`, if present.
+    pub fn with(&self) -> Option<&syn::Type> {
+        self.with.as_ref().map(|with| &*with.ty)
+    }
+
+    /// Get the `#[scale_info(rename = "..")]` attribute, if present.
+    pub fn rename(&self) -> Option<&RenameAttr> {
+        self.rename.as_ref()
+    }
+
+    /// Returns `true` if `#[scale_info(skip)]` is present.
+    pub fn skip(&self) -> bool {
+        self.skip
+    }
+}
+
+/// Returns `true` if the item is a struct with exactly one field which is not skipped.
+fn has_single_field(item: &syn::DeriveInput) -> bool {
+    match item.data {
+        syn::Data::Struct(ref data_struct) => {
+            data_struct
+                .fields
+                .iter()
+                .filter(|f| !crate::utils::should_skip_field(f))
+                .count()
+                == 1
+        }
+        _ => false,
+    }
 }
 
 /// Parsed representation of the `#[scale_info(bounds(...))]` attribute.
@@ -307,6 +500,175 @@ impl Parse for ReplaceSegment {
     }
 }
 
+/// Parsed representation of the `#[scale_info(transparent)]` attribute.
+#[derive(Clone)]
+pub struct TransparentAttr {
+    identity: bool,
+}
+
+impl TransparentAttr {
+    /// Returns `true` for `#[scale_info(transparent(identity))]`, i.e. if the `Identity` of the
+    /// type should be the type of its field.
+    pub fn identity(&self) -> bool {
+        self.identity
+    }
+}
+
+impl Parse for TransparentAttr {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        input.parse::<keywords::transparent>()?;
+        let identity = if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            content.parse::<keywords::identity>()?;
+            true
+        } else {
+            false
+        };
+
+        Ok(Self { identity })
+    }
+}
+
+/// Parsed representation of the `#[scale_info(rename = "..")]` attribute.
+#[derive(Clone)]
+pub struct RenameAttr {
+    name: LitStr,
+}
+
+impl RenameAttr {
+    pub fn name(&self) -> &LitStr {
+        &self.name
+    }
+}
+
+impl Parse for RenameAttr {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        input.parse::<keywords::rename>()?;
+        input.parse::<Token![=]>()?;
+        let name = input.parse::<LitStr>()?;
+
+        Ok(Self { name })
+    }
+}
+
+/// Parsed representation of the `#[scale_info(rename_all = "..")]` attribute.
+#[derive(Clone, Copy)]
+pub enum RenameAllAttr {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameAllAttr {
+    /// Apply the rule to the name of a field, which is expected to be in `snake_case`.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => lowercase_first(&Self::Pascal.apply_to_field(field)),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake.apply_to_field(field).replace('_', "-"),
+        }
+    }
+
+    /// Apply the rule to the name of a variant, which is expected to be in `PascalCase`.
+    pub fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => lowercase_first(variant),
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+impl Parse for RenameAllAttr {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        input.parse::<keywords::rename_all>()?;
+        input.parse::<Token![=]>()?;
+        let rename_all_lit = input.parse::<LitStr>()?;
+
+        match rename_all_lit.value().as_str() {
+            "lowercase" => Ok(Self::Lower),
+            "UPPERCASE" => Ok(Self::Upper),
+            "PascalCase" => Ok(Self::Pascal),
+            "camelCase" => Ok(Self::Camel),
+            "snake_case" => Ok(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            "kebab-case" => Ok(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Ok(Self::ScreamingKebab),
+            _ => Err(syn::Error::new_spanned(
+                rename_all_lit,
+                r#"Invalid rename_all value. Expected one of: "lowercase", "UPPERCASE", "PascalCase", "camelCase", "snake_case", "SCREAMING_SNAKE_CASE", "kebab-case", "SCREAMING-KEBAB-CASE""#,
+            )),
+        }
+    }
+}
+
+/// Parsed representation of the `#[scale_info(with = $ty)]` attribute.
+#[derive(Clone)]
+pub struct WithAttr {
+    span: proc_macro2::Span,
+    ty: Box<syn::Type>,
+}
+
+impl Parse for WithAttr {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let keyword = input.parse::<keywords::with>()?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+
+        Ok(Self {
+            span: keyword.span,
+            ty,
+        })
+    }
+}
+
 /// Parsed representation of one of the `#[scale_info(..)]` attributes.
 pub enum ScaleInfoAttr {
     Bounds(BoundsAttr),
@@ -314,6 +676,9 @@ pub enum ScaleInfoAttr {
     CaptureDocs(CaptureDocsAttr),
     CratePath(CratePathAttr),
     ReplaceSegment(ReplaceSegment),
+    Transparent(TransparentAttr),
+    Rename(RenameAttr),
+    RenameAll(RenameAllAttr),
 }
 
 impl Parse for ScaleInfoAttr {
@@ -329,6 +694,35 @@ impl Parse for ScaleInfoAttr {
             Ok(Self::CratePath(input.parse()?))
         } else if lookahead.peek(keywords::replace_segment) {
             Ok(Self::ReplaceSegment(input.parse()?))
+        } else if lookahead.peek(keywords::transparent) {
+            Ok(Self::Transparent(input.parse()?))
+        } else if lookahead.peek(keywords::rename_all) {
+            Ok(Self::RenameAll(input.parse()?))
+        } else if lookahead.peek(keywords::rename) {
+            Ok(Self::Rename(input.parse()?))
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+/// Parsed representation of one of the `#[scale_info(..)]` attributes of a field or a variant.
+enum FieldAttr {
+    With(WithAttr),
+    Rename(RenameAttr),
+    Skip,
+}
+
+impl Parse for FieldAttr {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(keywords::with) {
+            Ok(Self::With(input.parse()?))
+        } else if lookahead.peek(keywords::rename) {
+            Ok(Self::Rename(input.parse()?))
+        } else if lookahead.peek(keywords::skip) {
+            input.parse::<keywords::skip>()?;
+            Ok(Self::Skip)
         } else {
             Err(lookahead.error())
         }
@@ -19,11 +19,12 @@ mod attr;
 mod trait_bounds;
 mod utils;
 
-use self::attr::{Attributes, CaptureDocsAttr, CratePathAttr};
+use self::attr::{Attributes, CaptureDocsAttr, CratePathAttr, FieldAttributes};
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
 use syn::{
+    ext::IdentExt,
     parse::{Error, Result},
     parse_quote,
     punctuated::Punctuated,
@@ -60,13 +61,17 @@ impl TypeInfoImpl {
     fn parse(input: TokenStream2) -> Result<Self> {
         let ast: DeriveInput = syn::parse2(input)?;
         let attrs = attr::Attributes::from_ast(&ast)?;
+        check_field_attributes(&ast.data)?;
 
         Ok(Self { ast, attrs })
     }
 
     fn expand(&self) -> Result<TokenStream2> {
         let ident = &self.ast.ident;
-        let ident_str = ident.to_string();
+        let ident_str = match self.attrs.rename() {
+            Some(rename) => rename.name().value(),
+            None => ident.to_string(),
+        };
         let scale_info = crate_path(self.attrs.crate_path())?;
 
         let where_clause = trait_bounds::make_where_clause(
@@ -79,6 +84,35 @@ impl TypeInfoImpl {
 
         let (impl_generics, ty_generics, _) = self.ast.generics.split_for_impl();
 
+        if let Some(transparent) = self.attrs.transparent() {
+            let field = match self.ast.data {
+                Data::Struct(ref s) => s.fields.iter().find(|f| !utils::should_skip_field(f)),
+                _ => None,
+            }
+            .expect("`transparent` is only accepted for structs with a single field; qed");
+            let (ty, _) = field_type(field);
+            let ty = if utils::is_compact(field) {
+                quote!(#scale_info::scale::Compact<#ty>)
+            } else {
+                quote!(#ty)
+            };
+            let identity = if transparent.identity() {
+                ty.clone()
+            } else {
+                quote!(Self)
+            };
+
+            return Ok(quote! {
+                #[automatically_derived]
+                impl #impl_generics #scale_info::TypeInfo for #ident #ty_generics #where_clause {
+                    type Identity = #identity;
+                    fn type_info() -> #scale_info::Type {
+                        <#ty as #scale_info::TypeInfo>::type_info()
+                    }
+                }
+            });
+        }
+
         let type_params = self.ast.generics.type_params().map(|tp| {
             let ty_ident = &tp.ident;
             let ty = if self
@@ -95,6 +129,10 @@ impl TypeInfoImpl {
             }
         });
 
+        if let Data::Enum(ref e) = self.ast.data {
+            check_hidden_variants(e)?;
+        }
+
         let build_type = match &self.ast.data {
             Data::Struct(ref s) => self.generate_composite_type(s, &scale_info),
             Data::Enum(ref e) => self.generate_variant_type(e, &scale_info),
@@ -155,42 +193,31 @@ impl TypeInfoImpl {
     }
 
     fn generate_fields(&self, fields: &Punctuated<Field, Comma>) -> Vec<TokenStream2> {
+        // As with serde, `rename_all` on an enum only renames its variants, not their fields.
+        let rename_all = match self.ast.data {
+            Data::Struct(_) => self.attrs.rename_all(),
+            _ => None,
+        };
         fields
             .iter()
-            .filter(|f| !utils::should_skip(&f.attrs))
+            .filter(|f| !utils::should_skip_field(f))
             .map(|f| {
-                let (ty, ident) = (&f.ty, &f.ident);
-                // Replace any field lifetime params with `static to prevent "unnecessary lifetime parameter"
-                // warning. Any lifetime parameters are specified as 'static in the type of the impl.
-                struct StaticLifetimesReplace;
-                impl VisitMut for StaticLifetimesReplace {
-                    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
-                        *lifetime = parse_quote!('static)
-                    }
-                }
-                let mut ty = match ty {
-                    // When a type is specified as part of a `macro_rules!`, the tokens passed to
-                    // the `TypeInfo` derive macro are a type `Group`, which is pretty printed with
-                    // invisible delimiters e.g. /*«*/ bool /*»*/. To avoid printing the delimiters
-                    // the inner type element is extracted.
-                    syn::Type::Group(group) => (*group.elem).clone(),
-                    _ => ty.clone(),
-                };
-                StaticLifetimesReplace.visit_type_mut(&mut ty);
-
-                let type_name = clean_type_string(&quote!(#ty).to_string());
+                let ident = &f.ident;
+                let (ty, type_name) = field_type(f);
                 let docs = self.generate_docs(&f.attrs);
                 let type_of_method = if utils::is_compact(f) {
                     quote!(compact)
                 } else {
                     quote!(ty)
                 };
-                let name = match utils::maybe_renamed(f) {
-                    Some(ident) => quote!(.name(#ident)),
-                    None => ident
-                        .as_ref()
-                        .map(|ident| quote!(.name(::core::stringify!(#ident))))
-                        .unwrap_or(quote!()),
+                let name = match (utils::maybe_renamed(&f.attrs), ident, rename_all) {
+                    (Some(name), _, _) => quote!(.name(#name)),
+                    (None, Some(ident), Some(rename_all)) => {
+                        let name = rename_all.apply_to_field(&ident.unraw().to_string());
+                        quote!(.name(#name))
+                    }
+                    (None, Some(ident), None) => quote!(.name(::core::stringify!(#ident))),
+                    (None, None, _) => quote!(),
                 };
                 quote!(
                     .field(|f| f
@@ -207,13 +234,23 @@ impl TypeInfoImpl {
     fn generate_variant_type(&self, data_enum: &DataEnum, scale_info: &syn::Path) -> TokenStream2 {
         let variants = &data_enum.variants;
 
+        // Variants hidden with `#[scale_info(skip)]` are still encoded, so unlike the variants
+        // skipped with `#[codec(skip)]` they keep their place in the implicit indices.
         let variants = variants
             .into_iter()
             .filter(|v| !utils::should_skip(&v.attrs))
             .enumerate()
+            .filter(|(_, v)| !utils::is_hidden(&v.attrs))
             .map(|(i, v)| {
                 let ident = &v.ident;
-                let v_name = quote! {::core::stringify!(#ident) };
+                let v_name = match (utils::maybe_renamed(&v.attrs), self.attrs.rename_all()) {
+                    (Some(name), _) => quote!(#name),
+                    (None, Some(rename_all)) => {
+                        let name = rename_all.apply_to_variant(&ident.unraw().to_string());
+                        quote!(#name)
+                    }
+                    (None, None) => quote! {::core::stringify!(#ident) },
+                };
                 let docs = self.generate_docs(&v.attrs);
                 let index = utils::variant_index(v, i);
 
@@ -295,6 +332,107 @@ impl TypeInfoImpl {
     }
 }
 
+/// Checks the `#[scale_info(...)]` attributes of every field and variant, which are otherwise
+/// only looked up when needed.
+fn check_field_attributes(data: &Data) -> Result<()> {
+    let check_fields = |fields: &Fields| {
+        fields
+            .iter()
+            .try_for_each(|f| FieldAttributes::from_attrs(&f.attrs).map(|_| ()))
+    };
+    match data {
+        Data::Struct(ref s) => check_fields(&s.fields),
+        Data::Enum(ref e) => e.variants.iter().try_for_each(|v| {
+            FieldAttributes::from_variant(v)?;
+            check_fields(&v.fields)
+        }),
+        Data::Union(_) => Ok(()),
+    }
+}
+
+/// Checks that hiding variants with `#[scale_info(skip)]` leaves the index of every variant
+/// unambiguous.
+fn check_hidden_variants(data_enum: &DataEnum) -> Result<()> {
+    let mut any_hidden = false;
+    for v in data_enum
+        .variants
+        .iter()
+        .filter(|v| utils::is_hidden(&v.attrs))
+    {
+        if utils::should_skip(&v.attrs) {
+            return Err(Error::new_spanned(
+                v,
+                "`#[scale_info(skip)]` cannot be combined with `#[codec(skip)]`: a variant which \
+                is not encoded has no index, and is already left out of the type information",
+            ));
+        }
+        any_hidden = true;
+    }
+    if !any_hidden {
+        return Ok(());
+    }
+
+    let mut indices: Vec<(u64, &Ident)> = Vec::new();
+    let variants = data_enum
+        .variants
+        .iter()
+        .filter(|v| !utils::should_skip(&v.attrs));
+    for (i, v) in variants.enumerate() {
+        // Indices given by discriminant expressions other than literals are not checked.
+        let index = match (utils::maybe_index(v), &v.discriminant) {
+            (Some(index), _) => u64::from(index),
+            (None, Some((_, syn::Expr::Lit(expr)))) => match expr.lit {
+                syn::Lit::Int(ref lit) => lit.base10_parse()?,
+                _ => continue,
+            },
+            (None, Some(_)) => continue,
+            (None, None) => i as u64,
+        };
+        if let Some((_, other)) = indices.iter().find(|(other, _)| *other == index) {
+            return Err(Error::new_spanned(
+                v,
+                format!(
+                    "Variant index {} is already used by `{}`, the indices of an enum with \
+                    variants hidden by `#[scale_info(skip)]` must be unique",
+                    index, other
+                ),
+            ));
+        }
+        indices.push((index, &v.ident));
+    }
+    Ok(())
+}
+
+/// Returns the type by which a field is described, along with the name of its own type.
+fn field_type(field: &Field) -> (syn::Type, String) {
+    // Replace any field lifetime params with `static to prevent "unnecessary lifetime parameter"
+    // warning. Any lifetime parameters are specified as 'static in the type of the impl.
+    struct StaticLifetimesReplace;
+    impl VisitMut for StaticLifetimesReplace {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            *lifetime = parse_quote!('static)
+        }
+    }
+    let mut ty = match field.ty {
+        // When a type is specified as part of a `macro_rules!`, the tokens passed to
+        // the `TypeInfo` derive macro are a type `Group`, which is pretty printed with
+        // invisible delimiters e.g. /*«*/ bool /*»*/. To avoid printing the delimiters
+        // the inner type element is extracted.
+        syn::Type::Group(ref group) => (*group.elem).clone(),
+        ref ty => ty.clone(),
+    };
+    StaticLifetimesReplace.visit_type_mut(&mut ty);
+
+    let type_name = clean_type_string(&quote!(#ty).to_string());
+    // A field described by another type, e.g. the type it is encoded as, keeps the
+    // name of its own type.
+    if let Some(mut type_override) = utils::type_override(field) {
+        StaticLifetimesReplace.visit_type_mut(&mut type_override);
+        ty = type_override;
+    }
+    (ty, type_name)
+}
+
 /// Get the name of a crate, to be robust against renamed dependencies.
 fn crate_name_path(name: &str) -> Result<syn::Path> {
     proc_macro_crate::crate_name(name)
@@ -16,6 +16,7 @@
 //!
 //! NOTE: The code here is copied verbatim from `parity-scale-codec-derive`.
 
+use crate::attr::FieldAttributes;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
@@ -82,35 +83,25 @@ pub fn is_compact(field: &syn::Field) -> bool {
     .is_some()
 }
 
-/// Look for a `#[codec(skip)]` in the given attributes.
-pub fn should_skip(attrs: &[Attribute]) -> bool {
-    codec_meta_item(attrs.iter(), |meta| {
-        if let Meta::Path(ref path) = meta {
-            if path.is_ident("skip") {
-                return Some(path.span());
-            }
-        }
-
-        None
-    })
-    .is_some()
-}
-
-/// Look for a `#[scale_info(rename = $str)]` outer attribute on the given `Field`.
-pub fn maybe_renamed(field: &syn::Field) -> Option<String> {
+/// Look for a `#[codec(encoded_as = "$EncodeAs")]` outer attribute on the given `Field`.
+/// If found, the value is expected to be parseable as a type (panics otherwise).
+pub fn maybe_encoded_as(field: &syn::Field) -> Option<syn::Type> {
     let outer_attrs = field
         .attrs
         .iter()
         .filter(|attr| attr.style == AttrStyle::Outer);
-    scale_info_meta_item(outer_attrs, |meta| {
+    codec_meta_item(outer_attrs, |meta| {
         if let Meta::NameValue(ref nv) = meta {
-            if nv.path.is_ident("rename") {
+            if nv.path.is_ident("encoded_as") {
                 if let Expr::Lit(ExprLit {
                     lit: Lit::Str(ref v),
                     ..
                 }) = nv.value
                 {
-                    return Some(v.value());
+                    let ty = v
+                        .parse::<syn::Type>()
+                        .expect("Internal error. `#[codec(encoded_as = …)]` attribute syntax must be checked in `parity-scale-codec`. This is a bug.");
+                    return Some(ty);
                 }
             }
         }
@@ -119,22 +110,63 @@ pub fn maybe_renamed(field: &syn::Field) -> Option<String> {
     })
 }
 
-fn codec_meta_item<'a, F, R, I, M>(itr: I, pred: F) -> Option<R>
-where
-    F: FnMut(M) -> Option<R> + Clone,
-    I: Iterator<Item = &'a Attribute>,
-    M: Parse,
-{
-    find_meta_item("codec", itr, pred)
+/// Look for a `#[codec(skip)]` in the given attributes.
+pub fn should_skip(attrs: &[Attribute]) -> bool {
+    codec_meta_item(attrs.iter(), |meta| {
+        if let Meta::Path(ref path) = meta {
+            if path.is_ident("skip") {
+                return Some(path.span());
+            }
+        }
+
+        None
+    })
+    .is_some()
+}
+
+/// Look for a `#[scale_info(skip)]` in the given attributes.
+pub fn is_hidden(attrs: &[Attribute]) -> bool {
+    field_attributes(attrs).skip()
 }
 
-fn scale_info_meta_item<'a, F, R, I, M>(itr: I, pred: F) -> Option<R>
+/// Returns `true` if the given `Field` is left out of the type information, either because it
+/// is not encoded (`#[codec(skip)]`) or because it is hidden (`#[scale_info(skip)]`).
+pub fn should_skip_field(field: &syn::Field) -> bool {
+    should_skip(&field.attrs) || is_hidden(&field.attrs)
+}
+
+/// Look for a `#[scale_info(rename = $str)]` attribute in the given attributes of a field or
+/// variant.
+pub fn maybe_renamed(attrs: &[Attribute]) -> Option<String> {
+    field_attributes(attrs)
+        .rename()
+        .map(|rename| rename.name().value())
+}
+
+/// Look for a `#[scale_info(with = $ty)]` attribute on the given `Field`.
+pub fn maybe_with(field: &syn::Field) -> Option<syn::Type> {
+    field_attributes(&field.attrs).with().cloned()
+}
+
+/// Returns the `#[scale_info(...)]` attributes of a field or a variant. Invalid attributes are
+/// reported when the derive input is parsed, so they are ignored here.
+fn field_attributes(attrs: &[Attribute]) -> FieldAttributes {
+    FieldAttributes::from_attrs(attrs).unwrap_or_default()
+}
+
+/// Returns the type describing the given `Field` in place of its own type, as set by
+/// `#[scale_info(with = $ty)]` or `#[codec(encoded_as = "$EncodeAs")]`.
+pub fn type_override(field: &syn::Field) -> Option<syn::Type> {
+    maybe_with(field).or_else(|| maybe_encoded_as(field))
+}
+
+fn codec_meta_item<'a, F, R, I, M>(itr: I, pred: F) -> Option<R>
 where
     F: FnMut(M) -> Option<R> + Clone,
     I: Iterator<Item = &'a Attribute>,
     M: Parse,
 {
-    find_meta_item("scale_info", itr, pred)
+    find_meta_item("codec", itr, pred)
 }
 
 fn find_meta_item<'a, F, R, I, M>(kind: &str, mut itr: I, mut pred: F) -> Option<R>
@@ -0,0 +1,189 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of source code from a [`PortableRegistry`](crate::PortableRegistry).
+//!
+//! - [`RustGenerator`] generates Rust types sharing the encoding of the registry types.
+//! - [`TypeScriptGenerator`] generates TypeScript declarations describing the values of the
+//!   registry types.
+//!
+//! Both only rely on the registry, so they can run over metadata decoded from SCALE bytes.
+
+use crate::prelude::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{form::PortableForm, Type};
+
+mod rust;
+mod typescript;
+
+pub use self::{rust::RustGenerator, typescript::TypeScriptGenerator};
+
+/// An error that may be encountered when generating code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodegenError {
+    /// The type id could not be found in the registry.
+    TypeNotFound(u32),
+    /// The type with the given id is a variant type without a path, which has no name to be
+    /// referred to by.
+    UnnamedVariant(u32),
+    /// The type with the given id refers back to itself without going through a named type,
+    /// so it has no finite rendering.
+    RecursiveType(u32),
+}
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeNotFound(id) => write!(f, "type with id {id} not found in the registry"),
+            Self::UnnamedVariant(id) => {
+                write!(f, "type with id {id} is a variant type without a path")
+            }
+            Self::RecursiveType(id) => {
+                write!(f, "type with id {id} refers back to itself without a path")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodegenError {}
+
+/// The types and submodules of a generated module or namespace.
+#[derive(Default)]
+struct Module {
+    types: Vec<u32>,
+    children: BTreeMap<String, Module>,
+}
+
+/// The context of the type being generated.
+struct Scope<'a> {
+    namespace: &'a [String],
+    /// The modules enclosing the type, starting with the root module, if known.
+    modules: &'a [&'a Module],
+    /// The names of the type parameters, with their concrete types.
+    params: Vec<(String, Option<u32>)>,
+    /// The type parameters which may be referred to by the current field.
+    allowed: BTreeSet<String>,
+    /// The type parameters referred to by any field.
+    used: BTreeSet<String>,
+    /// The ids of the types being rendered, to detect types referring back to themselves.
+    visiting: BTreeSet<u32>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(namespace: &'a [String], ty: &Type<PortableForm>) -> Self {
+        Self {
+            namespace,
+            modules: &[],
+            params: ty
+                .type_params
+                .iter()
+                .map(|p| (to_string(&p.name), p.ty.map(|ty| ty.id)))
+                .collect(),
+            allowed: BTreeSet::new(),
+            used: BTreeSet::new(),
+            visiting: BTreeSet::new(),
+        }
+    }
+
+    /// Sets the modules enclosing the type, starting with the root module.
+    fn within(mut self, modules: &'a [&'a Module]) -> Self {
+        self.modules = modules;
+        self
+    }
+
+    /// Returns the names of the type parameters.
+    fn param_names(&self) -> Vec<&str> {
+        self.params.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Allows referring to the type parameters appearing in the type name of the current
+    /// field, or to all of them if the type name is unknown.
+    fn allow_params(&mut self, type_name: Option<&str>) {
+        let idents: BTreeSet<_> = match type_name {
+            Some(type_name) => type_name
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .collect(),
+            None => self.params.iter().map(|(name, _)| name.as_str()).collect(),
+        };
+        self.allowed = self
+            .params
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| idents.contains(name.as_str()))
+            .cloned()
+            .collect();
+    }
+
+    /// Returns the type parameter standing for the type with the given id, if any.
+    fn param(&mut self, id: u32) -> Option<String> {
+        let (name, _) = self
+            .params
+            .iter()
+            .find(|(name, ty)| *ty == Some(id) && self.allowed.contains(name))?;
+        self.used.insert(name.clone());
+        Some(name.clone())
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    indent: usize,
+}
+
+impl Writer {
+    /// Returns an empty writer indented one level deeper.
+    fn nested(&self) -> Self {
+        Self {
+            out: String::new(),
+            indent: self.indent + 1,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.out.push_str("    ");
+            }
+            self.out.push_str(line);
+        }
+        self.out.push('\n');
+    }
+}
+
+/// Returns the segments of the namespace of the type relative to the given namespace, i.e.
+/// the number of namespaces to leave and the namespaces to enter from there.
+fn relative_namespace(namespace: &[String], ty: &Type<PortableForm>) -> (usize, Vec<String>) {
+    let target: Vec<String> = ty.path.namespace().iter().map(to_string).collect();
+    let common = namespace
+        .iter()
+        .zip(&target)
+        .take_while(|(a, b)| a == b)
+        .count();
+    (namespace.len() - common, target[common..].to_vec())
+}
+
+fn path_string(ty: &Type<PortableForm>) -> String {
+    ty.path.to_string()
+}
+
+fn to_string<S: AsRef<str>>(s: &S) -> String {
+    s.as_ref().to_string()
+}
@@ -0,0 +1,543 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of Rust source code.
+
+use crate::prelude::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use super::{path_string, relative_namespace, to_string, CodegenError, Module, Scope, Writer};
+use crate::{
+    form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive, Variant,
+};
+
+/// Generates Rust source code for the types of a [`PortableRegistry`].
+///
+/// Every composite and variant type with a [`Path`](crate::Path) becomes a struct or an enum
+/// deriving `Encode`, `Decode` and `TypeInfo`, placed in nested modules following the
+/// namespace of its path. Other types are rendered inline where they are used.
+///
+/// - Type parameters become generics. Fields whose type is the concrete type of a type
+///   parameter refer to the generic instead, unless the field type name shows otherwise.
+///   Generics not used by any field are kept in a skipped `PhantomData` field or variant.
+/// - Each variant gets an explicit `#[codec(index = ..)]`.
+/// - Field and variant names which are not valid identifiers, e.g. keywords or `kebab-case`
+///   names, are turned into identifiers with a `#[scale_info(rename = ..)]` keeping the name.
+/// - Fields of a [`TypeDefCompact`](crate::TypeDefCompact) type get `#[codec(compact)]`.
+/// - Types of the root namespace, e.g. `Option` and `BTreeMap`, refer to the matching
+///   standard library types.
+/// - `U256`/`I256` are rendered as `[u8; 32]`, and `char` as `u32`, which share their
+///   encoding.
+///
+/// The generated code refers to other generated types through `super::` paths, so it can be
+/// placed in any module.
+///
+/// # Example
+///
+/// ```
+/// # use scale_info::{codegen::RustGenerator, MetaType, PortableRegistry, Registry};
+/// let mut registry = Registry::new();
+/// registry.register_type(&MetaType::new::<Option<(u8, bool)>>());
+/// let registry: PortableRegistry = registry.into();
+///
+/// let code = RustGenerator::new().derive("Debug").generate(&registry).unwrap();
+/// // `Option` is a standard library type, so there is nothing to generate.
+/// assert_eq!(code, "");
+/// ```
+#[derive(Clone, Debug)]
+pub struct RustGenerator {
+    codec_crate: String,
+    scale_info_crate: String,
+    derives: Vec<String>,
+    substitutes: BTreeMap<String, String>,
+}
+
+impl Default for RustGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RustGenerator {
+    /// Creates a generator referring to the `parity-scale-codec` and `scale-info` crates by
+    /// their default names.
+    pub fn new() -> Self {
+        Self {
+            codec_crate: "::parity_scale_codec".into(),
+            scale_info_crate: "::scale_info".into(),
+            derives: Vec::new(),
+            substitutes: BTreeMap::new(),
+        }
+        .substitute("bitvec::order::Lsb0", "::bitvec::order::Lsb0")
+        .substitute("bitvec::order::Msb0", "::bitvec::order::Msb0")
+    }
+
+    /// Sets the path to the `parity-scale-codec` crate, e.g. `::codec` if it is renamed.
+    pub fn codec_crate(mut self, path: &str) -> Self {
+        self.codec_crate = path.into();
+        self
+    }
+
+    /// Sets the path to the `scale-info` crate.
+    pub fn scale_info_crate(mut self, path: &str) -> Self {
+        self.scale_info_crate = path.into();
+        self
+    }
+
+    /// Adds a derive to all generated types, e.g. `Debug`.
+    pub fn derive(mut self, path: &str) -> Self {
+        self.derives.push(path.into());
+        self
+    }
+
+    /// Uses an existing Rust type instead of generating the type with the given path, e.g.
+    /// `substitute("sp_core::crypto::AccountId32", "::my_crate::AccountId")`.
+    ///
+    /// Any type parameters of the type are passed as generics to the substitute.
+    pub fn substitute(mut self, path: &str, with: &str) -> Self {
+        self.substitutes.insert(path.into(), with.into());
+        self
+    }
+
+    /// Returns the Rust source code of all the types to generate from the registry.
+    ///
+    /// Fails if a type refers to a type id missing from the registry, to a variant type without
+    /// a path, which can't be rendered with the same encoding, or to a type without a path
+    /// referring back to itself.
+    pub fn generate(&self, registry: &PortableRegistry) -> Result<String, CodegenError> {
+        let mut root = Module::default();
+        let mut seen = BTreeSet::new();
+        for ty in &registry.types {
+            if self.generates(&ty.ty) && seen.insert(path_string(&ty.ty)) {
+                let mut module = &mut root;
+                for segment in ty.ty.path.namespace() {
+                    module = module.children.entry(to_string(segment)).or_default();
+                }
+                module.types.push(ty.id);
+            }
+        }
+
+        let mut writer = Writer::default();
+        self.write_module(&mut writer, registry, &root, &mut Vec::new())?;
+        Ok(writer.out)
+    }
+
+    /// Returns `true` if a definition is generated for the type, rather than rendering it
+    /// inline or referring to an existing type.
+    fn generates(&self, ty: &Type<PortableForm>) -> bool {
+        matches!(ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_))
+            && !ty.path.namespace().is_empty()
+            && !self.substitutes.contains_key(&path_string(ty))
+    }
+
+    fn write_module(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        module: &Module,
+        namespace: &mut Vec<String>,
+    ) -> Result<(), CodegenError> {
+        for (i, id) in module.types.iter().enumerate() {
+            if i > 0 {
+                writer.line("");
+            }
+            let ty = registry
+                .resolve(*id)
+                .expect("ids are taken from the registry; qed");
+            self.write_type(writer, registry, ty, namespace)?;
+        }
+        for (i, (name, child)) in module.children.iter().enumerate() {
+            if i > 0 || !module.types.is_empty() {
+                writer.line("");
+            }
+            writer.line(&format!("pub mod {name} {{"));
+            writer.indent += 1;
+            namespace.push(name.clone());
+            self.write_module(writer, registry, child, namespace)?;
+            namespace.pop();
+            writer.indent -= 1;
+            writer.line("}");
+        }
+        Ok(())
+    }
+
+    fn write_type(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        ty: &Type<PortableForm>,
+        namespace: &[String],
+    ) -> Result<(), CodegenError> {
+        let ident = ty.path.ident().map(|s| to_string(&s)).unwrap_or_default();
+        let mut scope = Scope::new(namespace, ty);
+
+        // Render the body first, to find out which generics are used.
+        let mut body = writer.nested();
+        let (keyword, mut tail) = match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                let tail =
+                    self.write_fields(&mut body, registry, &composite.fields, "pub ", &mut scope)?;
+                ("struct", tail)
+            }
+            TypeDef::Variant(variant) => {
+                self.write_variants(&mut body, registry, &variant.variants, &mut scope)?;
+                ("enum", Tail::Block)
+            }
+            _ => unreachable!("only composites and variants are generated; qed"),
+        };
+        let unused: Vec<_> = scope
+            .param_names()
+            .into_iter()
+            .filter(|name| !scope.used.contains(*name))
+            .collect();
+        if !unused.is_empty() {
+            let phantom = format!("::core::marker::PhantomData<({},)>", unused.join(", "));
+            body.line("#[codec(skip)]");
+            match (keyword, &tail) {
+                ("enum", _) => body.line(&format!("__Ignore({phantom}),")),
+                (_, Tail::Block) => body.line(&format!("pub __phantom: {phantom},")),
+                _ => {
+                    body.line(&format!("pub {phantom},"));
+                    tail = Tail::Tuple;
+                }
+            }
+        }
+
+        for doc in &ty.docs {
+            writer.line(&doc_comment(doc));
+        }
+        let mut derives = Vec::new();
+        derives.push(format!("{}::Encode", self.codec_crate));
+        derives.push(format!("{}::Decode", self.codec_crate));
+        derives.push(format!("{}::TypeInfo", self.scale_info_crate));
+        derives.extend(self.derives.iter().cloned());
+        writer.line(&format!("#[derive({})]", derives.join(", ")));
+        if self.codec_crate != "::parity_scale_codec" {
+            writer.line(&format!("#[codec(crate = {})]", self.codec_crate));
+        }
+        if self.scale_info_crate != "::scale_info" {
+            writer.line(&format!("#[scale_info(crate = {})]", self.scale_info_crate));
+        }
+        let generics = if scope.params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", scope.param_names().join(", "))
+        };
+        let header = format!("pub {keyword} {ident}{generics}");
+        match tail {
+            Tail::Unit => writer.line(&format!("{header};")),
+            Tail::Block => {
+                writer.line(&format!("{header} {{"));
+                writer.out.push_str(&body.out);
+                writer.line("}");
+            }
+            Tail::Tuple => {
+                writer.line(&format!("{header}("));
+                writer.out.push_str(&body.out);
+                writer.line(");");
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the fields of a struct or variant, returning how they are delimited.
+    fn write_fields(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        fields: &[Field<PortableForm>],
+        vis: &str,
+        scope: &mut Scope,
+    ) -> Result<Tail, CodegenError> {
+        let named = fields.iter().all(|f| f.name.is_some());
+        let mut idents = BTreeSet::new();
+        for field in fields {
+            for doc in &field.docs {
+                writer.line(&doc_comment(doc));
+            }
+            scope.allow_params(field.type_name.as_ref().map(to_string).as_deref());
+            let field_ty = match registry.resolve(field.ty.id).map(|ty| &ty.type_def) {
+                Some(TypeDef::Compact(compact)) => {
+                    writer.line("#[codec(compact)]");
+                    self.type_name(registry, compact.type_param.id, scope)?
+                }
+                _ => self.type_name(registry, field.ty.id, scope)?,
+            };
+            match &field.name {
+                Some(name) if named => {
+                    let ident = rust_ident(name.as_ref(), &mut idents, writer);
+                    writer.line(&format!("{vis}{ident}: {field_ty},"))
+                }
+                _ => writer.line(&format!("{vis}{field_ty},")),
+            }
+        }
+        Ok(match (fields.is_empty(), named) {
+            (true, _) => Tail::Unit,
+            (false, true) => Tail::Block,
+            (false, false) => Tail::Tuple,
+        })
+    }
+
+    fn write_variants(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        variants: &[Variant<PortableForm>],
+        scope: &mut Scope,
+    ) -> Result<(), CodegenError> {
+        let mut idents = BTreeSet::new();
+        for variant in variants {
+            for doc in &variant.docs {
+                writer.line(&doc_comment(doc));
+            }
+            writer.line(&format!("#[codec(index = {})]", variant.index));
+            let name = rust_ident(variant.name.as_ref(), &mut idents, writer);
+            let mut fields = writer.nested();
+            match self.write_fields(&mut fields, registry, &variant.fields, "", scope)? {
+                Tail::Unit => writer.line(&format!("{name},")),
+                Tail::Block => {
+                    writer.line(&format!("{name} {{"));
+                    writer.out.push_str(&fields.out);
+                    writer.line("},");
+                }
+                Tail::Tuple => {
+                    writer.line(&format!("{name}("));
+                    writer.out.push_str(&fields.out);
+                    writer.line("),");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the Rust type referring to the type with the given id from within the scope.
+    fn type_name(
+        &self,
+        registry: &PortableRegistry,
+        id: u32,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        if let Some(param) = scope.param(id) {
+            return Ok(param);
+        }
+        if !scope.visiting.insert(id) {
+            return Err(CodegenError::RecursiveType(id));
+        }
+        let name = self.render_type(registry, id, scope);
+        scope.visiting.remove(&id);
+        name
+    }
+
+    /// Renders the type with the given id, which is not a type parameter.
+    fn render_type(
+        &self,
+        registry: &PortableRegistry,
+        id: u32,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        let ty = registry.resolve(id).ok_or(CodegenError::TypeNotFound(id))?;
+
+        if !ty.path.is_empty() {
+            let path = path_string(ty);
+            let base = if let Some(substitute) = self.substitutes.get(&path) {
+                Some(substitute.clone())
+            } else if self.generates(ty) {
+                Some(relative_path(scope.namespace, ty))
+            } else if ty.path.namespace().is_empty() {
+                Some(prelude_path(&path))
+            } else {
+                None
+            };
+            if let Some(base) = base {
+                let args: Vec<_> = ty
+                    .type_params
+                    .iter()
+                    .map(|param| match param.ty {
+                        Some(param) => self.type_name(registry, param.id, scope),
+                        None => Ok("()".into()),
+                    })
+                    .collect::<Result<_, _>>()?;
+                return Ok(match (base.as_str(), args.is_empty()) {
+                    (_, true) => base,
+                    ("::std::borrow::Cow", false) => {
+                        format!("{base}<'static, {}>", args.join(", "))
+                    }
+                    (_, false) => format!("{base}<{}>", args.join(", ")),
+                });
+            }
+        }
+
+        Ok(match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                // A type with a namespace which is neither generated nor substituted can't
+                // be referred to, so fall back to its fields.
+                let fields: Vec<_> = composite.fields.iter().map(|f| f.ty.id).collect();
+                self.tuple_name(registry, &fields, scope)?
+            }
+            TypeDef::Variant(_) => return Err(CodegenError::UnnamedVariant(id)),
+            TypeDef::Sequence(sequence) => format!(
+                "::std::vec::Vec<{}>",
+                self.type_name(registry, sequence.type_param.id, scope)?
+            ),
+            TypeDef::Array(array) => format!(
+                "[{}; {}]",
+                self.type_name(registry, array.type_param.id, scope)?,
+                array.len
+            ),
+            TypeDef::Tuple(tuple) => {
+                let fields: Vec<_> = tuple.fields.iter().map(|ty| ty.id).collect();
+                self.tuple_name(registry, &fields, scope)?
+            }
+            TypeDef::Primitive(primitive) => primitive_name(primitive).into(),
+            TypeDef::Compact(compact) => format!(
+                "{}::Compact<{}>",
+                self.codec_crate,
+                self.type_name(registry, compact.type_param.id, scope)?
+            ),
+            TypeDef::BitSequence(bit_sequence) => format!(
+                "::bitvec::vec::BitVec<{}, {}>",
+                self.type_name(registry, bit_sequence.bit_store_type.id, scope)?,
+                self.type_name(registry, bit_sequence.bit_order_type.id, scope)?
+            ),
+        })
+    }
+
+    fn tuple_name(
+        &self,
+        registry: &PortableRegistry,
+        ids: &[u32],
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        let names: Vec<_> = ids
+            .iter()
+            .map(|id| self.type_name(registry, *id, scope))
+            .collect::<Result<_, _>>()?;
+        Ok(match names.len() {
+            1 => format!("({},)", names[0]),
+            _ => format!("({})", names.join(", ")),
+        })
+    }
+}
+
+/// How the fields of a struct or variant are delimited.
+enum Tail {
+    Unit,
+    Block,
+    Tuple,
+}
+
+/// Returns the path of the type relative to the given module.
+fn relative_path(namespace: &[String], ty: &Type<PortableForm>) -> String {
+    let (up, down) = relative_namespace(namespace, ty);
+    let mut segments: Vec<String> = (0..up).map(|_| "super".into()).collect();
+    segments.extend(down);
+    segments.extend(ty.path.ident().map(|s| to_string(&s)));
+    segments.join("::")
+}
+
+/// Returns the path of a type of the root namespace, as registered by the `TypeInfo`
+/// implementations of this crate.
+fn prelude_path(ident: &str) -> String {
+    let path = match ident {
+        "Option" => "::core::option::Option",
+        "Result" => "::core::result::Result",
+        "PhantomData" => "::core::marker::PhantomData",
+        "Range" => "::core::ops::Range",
+        "RangeInclusive" => "::core::ops::RangeInclusive",
+        "Duration" => "::core::time::Duration",
+        "Cow" => "::std::borrow::Cow",
+        "BTreeMap" => "::std::collections::BTreeMap",
+        "BTreeSet" => "::std::collections::BTreeSet",
+        "BinaryHeap" => "::std::collections::BinaryHeap",
+        ident if ident.starts_with("NonZero") => return format!("::core::num::{ident}"),
+        ident => ident,
+    };
+    path.into()
+}
+
+/// Returns the identifier of a field or variant with the given name, unique among the given
+/// identifiers. Keywords become raw identifiers, and any character not allowed in an
+/// identifier is replaced by `_`. If the identifier differs from the name, a `rename`
+/// attribute keeping the name in the type information is written first.
+fn rust_ident(name: &str, idents: &mut BTreeSet<String>, writer: &mut Writer) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') || ident == "_" {
+        ident.insert(0, '_');
+    }
+    // Keywords which can't be raw identifiers.
+    if matches!(ident.as_str(), "crate" | "self" | "Self" | "super") {
+        ident.push('_');
+    }
+    let base = ident.clone();
+    let mut suffix = 1;
+    while !idents.insert(ident.clone()) {
+        ident = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    // The derive names a field or variant after its identifier, including any `r#` prefix.
+    if ident != name {
+        writer.line(&format!("#[scale_info(rename = {name:?})]"));
+    }
+    ident
+}
+
+/// The strict and reserved keywords of Rust, as of the 2021 edition.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+fn primitive_name(primitive: &TypeDefPrimitive) -> &'static str {
+    match primitive {
+        TypeDefPrimitive::Bool => "bool",
+        // `char` has no SCALE encoding of its own, but is described as a `u32`.
+        TypeDefPrimitive::Char => "u32",
+        TypeDefPrimitive::Str => "::std::string::String",
+        TypeDefPrimitive::U8 => "u8",
+        TypeDefPrimitive::U16 => "u16",
+        TypeDefPrimitive::U32 => "u32",
+        TypeDefPrimitive::U64 => "u64",
+        TypeDefPrimitive::U128 => "u128",
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => "[u8; 32]",
+        TypeDefPrimitive::I8 => "i8",
+        TypeDefPrimitive::I16 => "i16",
+        TypeDefPrimitive::I32 => "i32",
+        TypeDefPrimitive::I64 => "i64",
+        TypeDefPrimitive::I128 => "i128",
+    }
+}
+
+fn doc_comment(doc: &str) -> String {
+    if doc.is_empty() {
+        "///".into()
+    } else if doc.starts_with(' ') {
+        format!("///{doc}")
+    } else {
+        format!("/// {doc}")
+    }
+}
@@ -0,0 +1,466 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of TypeScript declarations.
+
+use crate::prelude::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use super::{path_string, relative_namespace, to_string, CodegenError, Module, Scope, Writer};
+use crate::{
+    form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive, Variant,
+};
+
+/// Generates TypeScript declarations for the types of a [`PortableRegistry`].
+///
+/// Every composite and variant type with a [`Path`](crate::Path) gets a declaration, placed in
+/// nested namespaces following the namespace of its path. Other types are rendered inline
+/// where they are used.
+///
+/// - Composites with named fields become interfaces. Composites with a single unnamed field
+///   are aliases of the type of that field, and those with several unnamed fields are
+///   aliases of tuples.
+/// - Variants become unions discriminated by a `type` property holding the variant name. The
+///   fields of a variant are held by a `value` property, as for composites.
+/// - Field names that are not identifiers, e.g. `amount-paid`, are quoted.
+/// - Declarations are referred to relative to the current namespace, qualified further where a
+///   nested namespace or declaration of the same name would shadow them.
+/// - Sequences and arrays become arrays, and bit sequences arrays of `boolean`.
+/// - Integers of 64 bits or more, including `U128` and `U256`, become `bigint`, and smaller
+///   integers `number`. Compact integers are rendered as the integers they wrap.
+/// - Unit types, e.g. `()`, become `null`.
+///
+/// # Example
+///
+/// ```
+/// # use scale_info::{codegen::TypeScriptGenerator, MetaType, PortableRegistry, Registry};
+/// let mut registry = Registry::new();
+/// registry.register_type(&MetaType::new::<Option<(u8, u128)>>());
+/// let registry: PortableRegistry = registry.into();
+///
+/// let code = TypeScriptGenerator::new().generate(&registry).unwrap();
+/// assert_eq!(
+///     code,
+///     r#"export type Option<T> =
+///     | { type: "None" }
+///     | { type: "Some"; value: T };
+/// "#
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TypeScriptGenerator {
+    substitutes: BTreeMap<String, String>,
+}
+
+impl TypeScriptGenerator {
+    /// Creates a generator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses an existing TypeScript type instead of generating the type with the given path,
+    /// e.g. `substitute("sp_core::crypto::AccountId32", "string")`.
+    ///
+    /// Any type parameters of the type are passed as generics to the substitute.
+    pub fn substitute(mut self, path: &str, with: &str) -> Self {
+        self.substitutes.insert(path.into(), with.into());
+        self
+    }
+
+    /// Returns the TypeScript declarations of all the types to generate from the registry.
+    ///
+    /// Fails if a type refers to a type without a path referring back to itself, which has no
+    /// finite rendering.
+    pub fn generate(&self, registry: &PortableRegistry) -> Result<String, CodegenError> {
+        // Bit order types only select how a bit sequence is encoded.
+        let bit_orders: BTreeSet<u32> = registry
+            .types
+            .iter()
+            .filter_map(|ty| match &ty.ty.type_def {
+                TypeDef::BitSequence(bit_sequence) => Some(bit_sequence.bit_order_type.id),
+                _ => None,
+            })
+            .collect();
+
+        let mut root = Module::default();
+        let mut seen = BTreeSet::new();
+        for ty in &registry.types {
+            if self.generates(&ty.ty)
+                && !bit_orders.contains(&ty.id)
+                && seen.insert(path_string(&ty.ty))
+            {
+                let mut module = &mut root;
+                for segment in ty.ty.path.namespace() {
+                    module = module.children.entry(to_string(segment)).or_default();
+                }
+                module.types.push(ty.id);
+            }
+        }
+
+        let mut writer = Writer::default();
+        self.write_namespace(
+            &mut writer,
+            registry,
+            &root,
+            &mut Vec::new(),
+            &mut crate::prelude::vec![&root],
+        )?;
+        Ok(writer.out)
+    }
+
+    /// Returns `true` if a declaration is generated for the type, rather than rendering it
+    /// inline or referring to an existing type.
+    fn generates(&self, ty: &Type<PortableForm>) -> bool {
+        matches!(ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_))
+            && !ty.path.is_empty()
+            && !self.substitutes.contains_key(&path_string(ty))
+    }
+
+    /// Writes the declarations of a namespace, given the namespaces enclosing it, starting
+    /// with the root namespace and ending with the namespace itself.
+    fn write_namespace<'a>(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        module: &'a Module,
+        namespace: &mut Vec<String>,
+        modules: &mut Vec<&'a Module>,
+    ) -> Result<(), CodegenError> {
+        for (i, id) in module.types.iter().enumerate() {
+            if i > 0 {
+                writer.line("");
+            }
+            let ty = registry
+                .resolve(*id)
+                .expect("ids are taken from the registry; qed");
+            self.write_type(writer, registry, ty, namespace, modules)?;
+        }
+        for (i, (name, child)) in module.children.iter().enumerate() {
+            if i > 0 || !module.types.is_empty() {
+                writer.line("");
+            }
+            writer.line(&format!("export namespace {name} {{"));
+            writer.indent += 1;
+            namespace.push(name.clone());
+            modules.push(child);
+            self.write_namespace(writer, registry, child, namespace, modules)?;
+            modules.pop();
+            namespace.pop();
+            writer.indent -= 1;
+            writer.line("}");
+        }
+        Ok(())
+    }
+
+    fn write_type(
+        &self,
+        writer: &mut Writer,
+        registry: &PortableRegistry,
+        ty: &Type<PortableForm>,
+        namespace: &[String],
+        modules: &[&Module],
+    ) -> Result<(), CodegenError> {
+        let ident = ty.path.ident().map(|s| to_string(&s)).unwrap_or_default();
+        let mut scope = Scope::new(namespace, ty).within(modules);
+        let generics = if scope.params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", scope.param_names().join(", "))
+        };
+
+        write_docs(writer, &ty.docs);
+        match &ty.type_def {
+            TypeDef::Composite(composite)
+                if !composite.fields.is_empty()
+                    && composite.fields.iter().all(|f| f.name.is_some()) =>
+            {
+                writer.line(&format!("export interface {ident}{generics} {{"));
+                let mut body = writer.nested();
+                for field in &composite.fields {
+                    write_docs(&mut body, &field.docs);
+                    let name = property_name(field);
+                    let field_ty = self.field_type(registry, field, &mut scope)?;
+                    body.line(&format!("{name}: {field_ty};"));
+                }
+                writer.out.push_str(&body.out);
+                writer.line("}");
+            }
+            TypeDef::Composite(composite) => {
+                let fields = self.fields_type(registry, &composite.fields, &mut scope)?;
+                writer.line(&format!("export type {ident}{generics} = {fields};"));
+            }
+            TypeDef::Variant(variant) if variant.variants.is_empty() => {
+                writer.line(&format!("export type {ident}{generics} = never;"));
+            }
+            TypeDef::Variant(variant) => {
+                writer.line(&format!("export type {ident}{generics} ="));
+                let mut body = writer.nested();
+                for variant in &variant.variants {
+                    write_docs(&mut body, &variant.docs);
+                    body.line(&format!(
+                        "| {}",
+                        self.variant_type(registry, variant, &mut scope)?
+                    ));
+                }
+                // Terminate the declaration after the last variant.
+                body.out.pop();
+                body.out.push_str(";\n");
+                writer.out.push_str(&body.out);
+            }
+            _ => unreachable!("only composites and variants are generated; qed"),
+        }
+        Ok(())
+    }
+
+    /// Returns the object type of a variant, discriminated by its name.
+    fn variant_type(
+        &self,
+        registry: &PortableRegistry,
+        variant: &Variant<PortableForm>,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        let name = to_string(&variant.name);
+        Ok(if variant.fields.is_empty() {
+            format!("{{ type: {name:?} }}")
+        } else {
+            let value = self.fields_type(registry, &variant.fields, scope)?;
+            format!("{{ type: {name:?}; value: {value} }}")
+        })
+    }
+
+    /// Returns the type of the value holding the given fields.
+    fn fields_type(
+        &self,
+        registry: &PortableRegistry,
+        fields: &[Field<PortableForm>],
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        let named = fields.iter().all(|f| f.name.is_some());
+        let types: Vec<_> = fields
+            .iter()
+            .map(|field| self.field_type(registry, field, scope))
+            .collect::<Result<_, _>>()?;
+        Ok(match (fields, named) {
+            ([], _) => "null".into(),
+            (_, true) => {
+                let members: Vec<_> = fields
+                    .iter()
+                    .zip(&types)
+                    .map(|(field, ty)| format!("{}: {ty}", property_name(field)))
+                    .collect();
+                format!("{{ {} }}", members.join("; "))
+            }
+            ([_], false) => types[0].clone(),
+            (_, false) => format!("[{}]", types.join(", ")),
+        })
+    }
+
+    fn field_type(
+        &self,
+        registry: &PortableRegistry,
+        field: &Field<PortableForm>,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        scope.allow_params(field.type_name.as_ref().map(to_string).as_deref());
+        self.type_name(registry, field.ty.id, scope)
+    }
+
+    /// Returns the TypeScript type referring to the type with the given id from within the
+    /// scope.
+    fn type_name(
+        &self,
+        registry: &PortableRegistry,
+        id: u32,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        if let Some(param) = scope.param(id) {
+            return Ok(param);
+        }
+        if !scope.visiting.insert(id) {
+            return Err(CodegenError::RecursiveType(id));
+        }
+        let name = self.render_type(registry, id, scope);
+        scope.visiting.remove(&id);
+        name
+    }
+
+    /// Renders the type with the given id, which is not a type parameter.
+    fn render_type(
+        &self,
+        registry: &PortableRegistry,
+        id: u32,
+        scope: &mut Scope,
+    ) -> Result<String, CodegenError> {
+        let ty = match registry.resolve(id) {
+            Some(ty) => ty,
+            None => return Ok("unknown".into()),
+        };
+
+        let base = if let Some(substitute) = self.substitutes.get(&path_string(ty)) {
+            Some(substitute.clone())
+        } else if self.generates(ty) {
+            Some(reference(registry, scope, ty))
+        } else {
+            None
+        };
+        if let Some(base) = base {
+            let args: Vec<_> = ty
+                .type_params
+                .iter()
+                .map(|param| match param.ty {
+                    Some(param) => self.type_name(registry, param.id, scope),
+                    None => Ok("unknown".into()),
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok(if args.is_empty() {
+                base
+            } else {
+                format!("{base}<{}>", args.join(", "))
+            });
+        }
+
+        Ok(match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                self.fields_type(registry, &composite.fields, scope)?
+            }
+            TypeDef::Variant(variant) if variant.variants.is_empty() => "never".into(),
+            TypeDef::Variant(variant) => {
+                let variants: Vec<_> = variant
+                    .variants
+                    .iter()
+                    .map(|variant| self.variant_type(registry, variant, scope))
+                    .collect::<Result<_, _>>()?;
+                format!("({})", variants.join(" | "))
+            }
+            TypeDef::Sequence(sequence) => {
+                format!(
+                    "{}[]",
+                    self.type_name(registry, sequence.type_param.id, scope)?
+                )
+            }
+            TypeDef::Array(array) => {
+                format!(
+                    "{}[]",
+                    self.type_name(registry, array.type_param.id, scope)?
+                )
+            }
+            TypeDef::Tuple(tuple) if tuple.fields.is_empty() => "null".into(),
+            TypeDef::Tuple(tuple) => {
+                let fields: Vec<_> = tuple
+                    .fields
+                    .iter()
+                    .map(|ty| self.type_name(registry, ty.id, scope))
+                    .collect::<Result<_, _>>()?;
+                format!("[{}]", fields.join(", "))
+            }
+            TypeDef::Primitive(primitive) => primitive_name(primitive).into(),
+            TypeDef::Compact(compact) => self.type_name(registry, compact.type_param.id, scope)?,
+            TypeDef::BitSequence(_) => "boolean[]".into(),
+        })
+    }
+}
+
+/// Returns the name referring to a generated type from within the scope.
+///
+/// The name is relative to the innermost namespace enclosing both, unless its first segment is
+/// also declared by a namespace nested in that one, which would shadow it. The name is then
+/// qualified further, up to the root namespace.
+fn reference(registry: &PortableRegistry, scope: &Scope, ty: &Type<PortableForm>) -> String {
+    let (up, down) = relative_namespace(scope.namespace, ty);
+    let common = scope.namespace.len() - up;
+    let mut segments: Vec<String> = scope.namespace[..common].to_vec();
+    segments.extend(down);
+    segments.extend(ty.path.ident().map(|s| to_string(&s)));
+
+    let start = (0..=common)
+        .rev()
+        .find(|start| {
+            let first = &segments[*start];
+            // A qualified name starts with a namespace, and a single name is a type.
+            let is_namespace = segments.len() - start > 1;
+            let nested = scope.modules.get(start + 1..).unwrap_or_default();
+            !nested.iter().any(|module| {
+                if is_namespace {
+                    module.children.contains_key(first)
+                } else {
+                    module.types.iter().any(|id| {
+                        registry
+                            .resolve(*id)
+                            .and_then(|ty| ty.path.ident())
+                            .map_or(false, |ident| AsRef::<str>::as_ref(&ident) == first)
+                    })
+                }
+            })
+        })
+        .unwrap_or(0);
+    segments[start..].join(".")
+}
+
+fn primitive_name(primitive: &TypeDefPrimitive) -> &'static str {
+    match primitive {
+        TypeDefPrimitive::Bool => "boolean",
+        TypeDefPrimitive::Char | TypeDefPrimitive::Str => "string",
+        TypeDefPrimitive::U8
+        | TypeDefPrimitive::U16
+        | TypeDefPrimitive::U32
+        | TypeDefPrimitive::I8
+        | TypeDefPrimitive::I16
+        | TypeDefPrimitive::I32 => "number",
+        // Beyond 53 bits, integers can't be represented exactly by a `number`.
+        TypeDefPrimitive::U64
+        | TypeDefPrimitive::U128
+        | TypeDefPrimitive::U256
+        | TypeDefPrimitive::I64
+        | TypeDefPrimitive::I128
+        | TypeDefPrimitive::I256 => "bigint",
+    }
+}
+
+/// Returns the name of a field as a property name, quoted if it is not an identifier.
+fn property_name(field: &Field<PortableForm>) -> String {
+    let name = field.name.as_ref().map(to_string).unwrap_or_default();
+    let mut chars = name.chars();
+    let is_identifier = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name
+    } else {
+        format!("{name:?}")
+    }
+}
+
+fn write_docs<S: AsRef<str>>(writer: &mut Writer, docs: &[S]) {
+    if docs.is_empty() {
+        return;
+    }
+    writer.line("/**");
+    for doc in docs {
+        // A `*/` in the docs would end the comment.
+        let doc = doc.as_ref().trim_end().replace("*/", "*\\/");
+        if doc.is_empty() {
+            writer.line(" *");
+        } else if doc.starts_with(' ') {
+            writer.line(&format!(" *{doc}"));
+        } else {
+            writer.line(&format!(" * {doc}"));
+        }
+    }
+    writer.line(" */");
+}
@@ -0,0 +1,794 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checking whether types from two registries share the same SCALE encoding.
+//!
+//! When a type evolves, e.g. across runtime upgrades, its type id, [`Path`](crate::Path), docs
+//! and field type names may change without affecting how its values are encoded.
+//! [`check_compatibility`] compares two types structurally, ignoring those, and reports every
+//! change which breaks decoding values encoded with the old type as the new type.
+//!
+//! Besides that:
+//!
+//! - Composites and tuples are compared as lists of fields, so a tuple struct is compatible
+//!   with a tuple of the same elements.
+//! - A composite or tuple with a single field is compatible with the type of that field.
+//! - Variants are matched by name, or by index for renamed variants. Adding variants is not
+//!   reported, since values of the old type can still be decoded.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{compat::{check_compatibility, IncompatibilityKind}, MetaType, PortableRegistry, Registry};
+//! fn registry_with<T: scale_info::TypeInfo + 'static>() -> (u32, PortableRegistry) {
+//!     let mut registry = Registry::new();
+//!     let id = registry.register_type(&MetaType::new::<T>()).id;
+//!     (id, registry.into())
+//! }
+//!
+//! let (old_id, old) = registry_with::<(u8, [u16; 2])>();
+//! let (new_id, new) = registry_with::<(u8, [u32; 2])>();
+//!
+//! let incompatibilities = check_compatibility(&old, old_id, &new, new_id);
+//! assert_eq!(incompatibilities.len(), 1);
+//! assert_eq!(incompatibilities[0].to_string(), "at .1[]: primitive changed from u16 to u32");
+//! ```
+
+use crate::prelude::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    form::PortableForm,
+    utils::{primitive_kind, type_def_kind},
+    Field, PortableRegistry, Type, TypeDef, TypeDefBitSequence, TypeDefPrimitive, Variant,
+};
+
+/// Compares the type with id `old_id` in the `old` registry against the type with id `new_id`
+/// in the `new` registry, returning every change which breaks decoding values encoded with the
+/// old type as the new type.
+///
+/// The types are compatible if the returned list is empty.
+pub fn check_compatibility(
+    old: &PortableRegistry,
+    old_id: u32,
+    new: &PortableRegistry,
+    new_id: u32,
+) -> Vec<Incompatibility> {
+    let mut checker = Checker {
+        old,
+        new,
+        stack: Vec::new(),
+        compatible: BTreeSet::new(),
+        cycles: 0,
+        path: Vec::new(),
+        incompatibilities: Vec::new(),
+    };
+    checker.check(old_id, new_id);
+    checker.incompatibilities
+}
+
+/// A change which breaks the encoding of a type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// The location of the change, starting from the root type.
+    pub path: Vec<PathSegment>,
+    /// The kind of change.
+    pub kind: IncompatibilityKind,
+}
+
+impl Display for Incompatibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.kind);
+        }
+        f.write_str("at ")?;
+        for segment in &self.path {
+            write!(f, "{segment}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// A segment of the path to a type nested within another type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A named field.
+    Field(String),
+    /// The position of an unnamed field or tuple element.
+    Index(usize),
+    /// An enum variant.
+    Variant(String),
+    /// The items of a sequence or array.
+    Item,
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{name}"),
+            Self::Index(index) => write!(f, ".{index}"),
+            Self::Variant(name) => write!(f, "::{name}"),
+            Self::Item => f.write_str("[]"),
+        }
+    }
+}
+
+/// The kind of change which breaks the encoding of a type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IncompatibilityKind {
+    /// The type id could not be found in the old registry.
+    OldTypeNotFound(u32),
+    /// The type id could not be found in the new registry.
+    NewTypeNotFound(u32),
+    /// The type changed to a different kind of type, e.g. from a sequence to an array.
+    KindChanged {
+        /// The kind of the old type.
+        old: &'static str,
+        /// The kind of the new type.
+        new: &'static str,
+    },
+    /// A named field was removed.
+    FieldRemoved(String),
+    /// A named field was added.
+    FieldAdded(String),
+    /// The named fields present in both types are in a different order.
+    FieldsReordered {
+        /// The field names of the old type.
+        old: Vec<String>,
+        /// The field names of the new type.
+        new: Vec<String>,
+    },
+    /// The number of unnamed fields or tuple elements changed.
+    FieldCountChanged {
+        /// The number of fields of the old type.
+        old: usize,
+        /// The number of fields of the new type.
+        new: usize,
+    },
+    /// A variant was removed.
+    VariantRemoved(String),
+    /// The index of a variant changed.
+    VariantIndexChanged {
+        /// The name of the variant.
+        name: String,
+        /// The index of the variant in the old type.
+        old: u8,
+        /// The index of the variant in the new type.
+        new: u8,
+    },
+    /// A primitive type changed, e.g. its width or signedness.
+    PrimitiveChanged {
+        /// The old primitive.
+        old: TypeDefPrimitive,
+        /// The new primitive.
+        new: TypeDefPrimitive,
+    },
+    /// The length of an array changed.
+    ArrayLengthChanged {
+        /// The old length.
+        old: u32,
+        /// The new length.
+        new: u32,
+    },
+    /// The type is compact encoded in one of the types but not in the other.
+    CompactnessChanged {
+        /// Whether the old type is compact encoded.
+        was_compact: bool,
+    },
+    /// The store type of a bit sequence changed.
+    BitStoreChanged {
+        /// The kind of the old store type.
+        old: &'static str,
+        /// The kind of the new store type.
+        new: &'static str,
+    },
+    /// The order type of a bit sequence changed.
+    BitOrderChanged {
+        /// The name of the old order type.
+        old: String,
+        /// The name of the new order type.
+        new: String,
+    },
+}
+
+impl Display for IncompatibilityKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OldTypeNotFound(id) => {
+                write!(f, "type with id {id} not found in the old registry")
+            }
+            Self::NewTypeNotFound(id) => {
+                write!(f, "type with id {id} not found in the new registry")
+            }
+            Self::KindChanged { old, new } => write!(f, "changed from {old} to {new}"),
+            Self::FieldRemoved(name) => write!(f, "field `{name}` was removed"),
+            Self::FieldAdded(name) => write!(f, "field `{name}` was added"),
+            Self::FieldsReordered { old, new } => {
+                write!(f, "fields reordered from {old:?} to {new:?}")
+            }
+            Self::FieldCountChanged { old, new } => {
+                write!(f, "number of fields changed from {old} to {new}")
+            }
+            Self::VariantRemoved(name) => write!(f, "variant `{name}` was removed"),
+            Self::VariantIndexChanged { name, old, new } => {
+                write!(f, "index of variant `{name}` changed from {old} to {new}")
+            }
+            Self::PrimitiveChanged { old, new } => write!(
+                f,
+                "primitive changed from {} to {}",
+                primitive_kind(old),
+                primitive_kind(new)
+            ),
+            Self::ArrayLengthChanged { old, new } => {
+                write!(f, "array length changed from {old} to {new}")
+            }
+            Self::CompactnessChanged { was_compact: true } => {
+                write!(f, "no longer compact encoded")
+            }
+            Self::CompactnessChanged { was_compact: false } => write!(f, "now compact encoded"),
+            Self::BitStoreChanged { old, new } => {
+                write!(f, "bit store type changed from {old} to {new}")
+            }
+            Self::BitOrderChanged { old, new } => {
+                write!(f, "bit order type changed from {old} to {new}")
+            }
+        }
+    }
+}
+
+struct Checker<'a> {
+    old: &'a PortableRegistry,
+    new: &'a PortableRegistry,
+    /// The pairs of type ids being compared, so that recursive types terminate.
+    stack: Vec<(u32, u32)>,
+    /// The pairs of type ids found to be compatible, which need not be compared again.
+    compatible: BTreeSet<(u32, u32)>,
+    /// The number of comparisons cut short by a recursive type. A pair compared without
+    /// reaching one of its ancestors again is compatible wherever it appears.
+    cycles: usize,
+    path: Vec<PathSegment>,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, kind: IncompatibilityKind) {
+        self.incompatibilities.push(Incompatibility {
+            path: self.path.clone(),
+            kind,
+        })
+    }
+
+    fn check_at(&mut self, segment: PathSegment, old_id: u32, new_id: u32) {
+        self.path.push(segment);
+        self.check(old_id, new_id);
+        self.path.pop();
+    }
+
+    fn check(&mut self, old_id: u32, new_id: u32) {
+        let pair = (old_id, new_id);
+        if self.stack.contains(&pair) {
+            // Any incompatibility is reported where the recursion started.
+            self.cycles += 1;
+            return;
+        }
+        if self.compatible.contains(&pair) {
+            return;
+        }
+        let (cycles, incompatibilities) = (self.cycles, self.incompatibilities.len());
+        self.stack.push(pair);
+        self.check_types(old_id, new_id);
+        self.stack.pop();
+        if self.cycles == cycles && self.incompatibilities.len() == incompatibilities {
+            self.compatible.insert(pair);
+        }
+    }
+
+    fn check_types(&mut self, old_id: u32, new_id: u32) {
+        let (old, new) = match (self.old.resolve(old_id), self.new.resolve(new_id)) {
+            (Some(old), Some(new)) => (old, new),
+            (None, _) => return self.report(IncompatibilityKind::OldTypeNotFound(old_id)),
+            (_, None) => return self.report(IncompatibilityKind::NewTypeNotFound(new_id)),
+        };
+
+        match (&old.type_def, &new.type_def) {
+            (TypeDef::Variant(old), TypeDef::Variant(new)) => {
+                self.check_variants(&old.variants, &new.variants)
+            }
+            (TypeDef::Sequence(old), TypeDef::Sequence(new)) => {
+                self.check_at(PathSegment::Item, old.type_param.id, new.type_param.id)
+            }
+            (TypeDef::Array(old), TypeDef::Array(new)) => {
+                if old.len != new.len {
+                    self.report(IncompatibilityKind::ArrayLengthChanged {
+                        old: old.len,
+                        new: new.len,
+                    })
+                }
+                self.check_at(PathSegment::Item, old.type_param.id, new.type_param.id)
+            }
+            (TypeDef::Primitive(old), TypeDef::Primitive(new)) => {
+                if old != new {
+                    self.report(IncompatibilityKind::PrimitiveChanged {
+                        old: old.clone(),
+                        new: new.clone(),
+                    })
+                }
+            }
+            (TypeDef::Compact(old), TypeDef::Compact(new)) => {
+                self.check(old.type_param.id, new.type_param.id)
+            }
+            (TypeDef::BitSequence(old), TypeDef::BitSequence(new)) => {
+                self.check_bit_sequences(old, new)
+            }
+            (old_def, new_def) => {
+                match (fields(old_def), fields(new_def)) {
+                    (Some(old_fields), Some(new_fields)) => {
+                        return self.check_fields(&old_fields, &new_fields)
+                    }
+                    // Single field wrappers encode exactly like their field.
+                    (Some(old_fields), None) if old_fields.len() == 1 => {
+                        return self.check(old_fields[0].1, new_id)
+                    }
+                    (None, Some(new_fields)) if new_fields.len() == 1 => {
+                        return self.check(old_id, new_fields[0].1)
+                    }
+                    _ => (),
+                }
+                let kind = match (old_def, new_def) {
+                    (TypeDef::Compact(_), _) => {
+                        IncompatibilityKind::CompactnessChanged { was_compact: true }
+                    }
+                    (_, TypeDef::Compact(_)) => {
+                        IncompatibilityKind::CompactnessChanged { was_compact: false }
+                    }
+                    _ => IncompatibilityKind::KindChanged {
+                        old: type_def_kind(old_def),
+                        new: type_def_kind(new_def),
+                    },
+                };
+                self.report(kind)
+            }
+        }
+    }
+
+    fn check_fields(&mut self, old: &[(Option<&str>, u32)], new: &[(Option<&str>, u32)]) {
+        let (old_names, new_names) = match (field_names(old), field_names(new)) {
+            (Some(old_names), Some(new_names)) if old_names != new_names => (old_names, new_names),
+            _ => {
+                if old.len() != new.len() {
+                    self.report(IncompatibilityKind::FieldCountChanged {
+                        old: old.len(),
+                        new: new.len(),
+                    })
+                }
+                for (index, (old_field, new_field)) in old.iter().zip(new).enumerate() {
+                    let segment = match old_field.0 {
+                        Some(name) => PathSegment::Field(name.to_string()),
+                        None => PathSegment::Index(index),
+                    };
+                    self.check_at(segment, old_field.1, new_field.1);
+                }
+                return;
+            }
+        };
+
+        // The field names differ, so compare the fields present in both types by name.
+        for name in &old_names {
+            if !new_names.contains(name) {
+                self.report(IncompatibilityKind::FieldRemoved(name.to_string()))
+            }
+        }
+        for name in &new_names {
+            if !old_names.contains(name) {
+                self.report(IncompatibilityKind::FieldAdded(name.to_string()))
+            }
+        }
+        let old_common = old_names.iter().filter(|name| new_names.contains(name));
+        let new_common = new_names.iter().filter(|name| old_names.contains(name));
+        if !old_common.eq(new_common) {
+            self.report(IncompatibilityKind::FieldsReordered {
+                old: old_names.iter().map(ToString::to_string).collect(),
+                new: new_names.iter().map(ToString::to_string).collect(),
+            })
+        }
+        for (name, old_id) in old {
+            let new_field = new.iter().find(|(new_name, _)| new_name == name);
+            if let (Some(name), Some((_, new_id))) = (name, new_field) {
+                self.check_at(PathSegment::Field(name.to_string()), *old_id, *new_id);
+            }
+        }
+    }
+
+    fn check_variants(&mut self, old: &[Variant<PortableForm>], new: &[Variant<PortableForm>]) {
+        for old_variant in old {
+            let name = as_str(&old_variant.name);
+            let new_variant = match new.iter().find(|v| as_str(&v.name) == name) {
+                Some(new_variant) => {
+                    if new_variant.index != old_variant.index {
+                        self.report(IncompatibilityKind::VariantIndexChanged {
+                            name: name.to_string(),
+                            old: old_variant.index,
+                            new: new_variant.index,
+                        })
+                    }
+                    new_variant
+                }
+                // A variant renamed without changing its index is still compatible.
+                None => match new.iter().find(|v| v.index == old_variant.index) {
+                    Some(new_variant) => new_variant,
+                    None => {
+                        self.report(IncompatibilityKind::VariantRemoved(name.to_string()));
+                        continue;
+                    }
+                },
+            };
+            self.path.push(PathSegment::Variant(name.to_string()));
+            self.check_fields(
+                &field_list(&old_variant.fields),
+                &field_list(&new_variant.fields),
+            );
+            self.path.pop();
+        }
+    }
+
+    fn check_bit_sequences(
+        &mut self,
+        old: &TypeDefBitSequence<PortableForm>,
+        new: &TypeDefBitSequence<PortableForm>,
+    ) {
+        let resolve = |registry: &'a PortableRegistry, id: u32| registry.resolve(id);
+        match (
+            resolve(self.old, old.bit_store_type.id),
+            resolve(self.new, new.bit_store_type.id),
+        ) {
+            (Some(old_store), Some(new_store)) => {
+                if old_store.type_def != new_store.type_def {
+                    self.report(IncompatibilityKind::BitStoreChanged {
+                        old: type_def_kind(&old_store.type_def),
+                        new: type_def_kind(&new_store.type_def),
+                    })
+                }
+            }
+            (None, _) => self.report(IncompatibilityKind::OldTypeNotFound(old.bit_store_type.id)),
+            (_, None) => self.report(IncompatibilityKind::NewTypeNotFound(new.bit_store_type.id)),
+        }
+        match (
+            resolve(self.old, old.bit_order_type.id),
+            resolve(self.new, new.bit_order_type.id),
+        ) {
+            (Some(old_order), Some(new_order)) => {
+                let (old_order, new_order) = (order_name(old_order), order_name(new_order));
+                if old_order != new_order {
+                    self.report(IncompatibilityKind::BitOrderChanged {
+                        old: old_order.to_string(),
+                        new: new_order.to_string(),
+                    })
+                }
+            }
+            (None, _) => self.report(IncompatibilityKind::OldTypeNotFound(old.bit_order_type.id)),
+            (_, None) => self.report(IncompatibilityKind::NewTypeNotFound(new.bit_order_type.id)),
+        }
+    }
+}
+
+/// Returns the names and type ids of the fields of a composite or the elements of a tuple.
+fn fields(type_def: &TypeDef<PortableForm>) -> Option<Vec<(Option<&str>, u32)>> {
+    match type_def {
+        TypeDef::Composite(composite) => Some(field_list(&composite.fields)),
+        TypeDef::Tuple(tuple) => Some(tuple.fields.iter().map(|ty| (None, ty.id)).collect()),
+        _ => None,
+    }
+}
+
+fn field_list(fields: &[Field<PortableForm>]) -> Vec<(Option<&str>, u32)> {
+    fields
+        .iter()
+        .map(|field| (field.name.as_ref().map(AsRef::as_ref), field.ty.id))
+        .collect()
+}
+
+/// Returns the names of the fields, if all fields are named.
+fn field_names<'a>(fields: &[(Option<&'a str>, u32)]) -> Option<Vec<&'a str>> {
+    fields.iter().map(|(name, _)| *name).collect()
+}
+
+fn as_str<S: AsRef<str>>(s: &S) -> &str {
+    s.as_ref()
+}
+
+/// Returns the name identifying a bit order type, ignoring its namespace.
+fn order_name(ty: &Type<PortableForm>) -> &str {
+    ty.path.segments.last().map(AsRef::as_ref).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale_info_derive::TypeInfo;
+
+    fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        (id, registry.into())
+    }
+
+    fn check<Old: TypeInfo + 'static, New: TypeInfo + 'static>() -> Vec<Incompatibility> {
+        let (old_id, old) = registry_with::<Old>();
+        let (new_id, new) = registry_with::<New>();
+        check_compatibility(&old, old_id, &new, new_id)
+    }
+
+    fn incompatibility(path: Vec<PathSegment>, kind: IncompatibilityKind) -> Incompatibility {
+        Incompatibility { path, kind }
+    }
+
+    #[allow(unused)]
+    mod v1 {
+        use super::*;
+
+        /// Docs of v1.
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub id: [u8; 32],
+            pub nonce: u32,
+            pub balance: Balance,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Balance(pub u64);
+
+        #[derive(TypeInfo)]
+        pub enum Call {
+            Transfer {
+                to: u32,
+                amount: u64,
+            },
+            Remark(Vec<u8>),
+            #[codec(index = 5)]
+            Kill,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct List {
+            pub head: u8,
+            pub tail: Option<Box<List>>,
+        }
+    }
+
+    #[allow(unused)]
+    mod v2 {
+        use super::*;
+
+        /// Docs of v2 differ.
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub id: AccountId,
+            pub nonce: u32,
+            pub balance: u64,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct AccountId(pub [u8; 32]);
+
+        #[derive(TypeInfo)]
+        pub struct Reordered {
+            pub nonce: u32,
+            pub id: [u8; 32],
+            pub extra: bool,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Compact {
+            pub id: [u8; 16],
+            #[codec(compact)]
+            pub nonce: u32,
+            pub balance: Balance,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Balance(pub u128);
+
+        #[derive(TypeInfo)]
+        pub enum Call {
+            #[codec(index = 3)]
+            Transfer {
+                to: u32,
+                amount: u64,
+            },
+            Remark(Vec<u8>),
+            Burn,
+            #[codec(index = 5)]
+            Destroy,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct List {
+            pub head: u16,
+            pub tail: Option<Box<List>>,
+        }
+    }
+
+    #[test]
+    fn ignores_paths_docs_and_newtypes() {
+        assert_eq!(check::<v1::Account, v2::Account>(), vec![]);
+        assert_eq!(check::<(u64,), v1::Balance>(), vec![]);
+        assert_eq!(check::<v1::Balance, u64>(), vec![]);
+    }
+
+    #[test]
+    fn reports_field_changes() {
+        use IncompatibilityKind::*;
+
+        assert_eq!(
+            check::<v1::Account, v2::Reordered>(),
+            vec![
+                incompatibility(vec![], FieldRemoved("balance".into())),
+                incompatibility(vec![], FieldAdded("extra".into())),
+                incompatibility(
+                    vec![],
+                    FieldsReordered {
+                        old: vec!["id".into(), "nonce".into(), "balance".into()],
+                        new: vec!["nonce".into(), "id".into(), "extra".into()],
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            check::<v1::Account, v2::Compact>(),
+            vec![
+                incompatibility(
+                    vec![PathSegment::Field("id".into())],
+                    ArrayLengthChanged { old: 32, new: 16 }
+                ),
+                incompatibility(
+                    vec![PathSegment::Field("nonce".into())],
+                    CompactnessChanged { was_compact: false }
+                ),
+                incompatibility(
+                    vec![PathSegment::Field("balance".into()), PathSegment::Index(0)],
+                    PrimitiveChanged {
+                        old: TypeDefPrimitive::U64,
+                        new: TypeDefPrimitive::U128
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_variant_changes() {
+        let incompatibilities = check::<v1::Call, v2::Call>();
+        assert_eq!(
+            incompatibilities,
+            vec![incompatibility(
+                vec![],
+                IncompatibilityKind::VariantIndexChanged {
+                    name: "Transfer".into(),
+                    old: 0,
+                    new: 3,
+                }
+            )]
+        );
+        assert_eq!(
+            check::<v2::Call, v1::Call>(),
+            vec![
+                incompatibility(
+                    vec![],
+                    IncompatibilityKind::VariantIndexChanged {
+                        name: "Transfer".into(),
+                        old: 3,
+                        new: 0,
+                    }
+                ),
+                incompatibility(vec![], IncompatibilityKind::VariantRemoved("Burn".into())),
+            ]
+        );
+        // Renamed variants are matched by index.
+        assert_eq!(
+            check::<Option<u8>, Result<u8, ()>>(),
+            vec![
+                incompatibility(
+                    vec![PathSegment::Variant("None".into())],
+                    IncompatibilityKind::FieldCountChanged { old: 0, new: 1 }
+                ),
+                incompatibility(
+                    vec![PathSegment::Variant("Some".into()), PathSegment::Index(0)],
+                    IncompatibilityKind::KindChanged {
+                        old: "u8",
+                        new: "tuple"
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_shared_types_at_every_location() {
+        let changed = |index| {
+            incompatibility(
+                vec![PathSegment::Index(index), PathSegment::Index(0)],
+                IncompatibilityKind::PrimitiveChanged {
+                    old: TypeDefPrimitive::U64,
+                    new: TypeDefPrimitive::U128,
+                },
+            )
+        };
+        assert_eq!(
+            check::<(v1::Balance, v1::Balance), (v2::Balance, v2::Balance)>(),
+            vec![changed(0), changed(1)]
+        );
+    }
+
+    #[test]
+    fn recursive_types_terminate() {
+        let incompatibilities = check::<v1::List, v2::List>();
+        assert_eq!(incompatibilities.len(), 1);
+        assert_eq!(
+            incompatibilities[0].to_string(),
+            "at .head: primitive changed from u8 to u16"
+        );
+        assert_eq!(check::<v1::List, v1::List>(), vec![]);
+    }
+
+    #[test]
+    fn reports_bit_sequence_changes() {
+        let mut builder = PortableRegistryBuilder::new();
+        let u8_id = builder.register_type(TypeDefPrimitive::U8.into());
+        let u32_id = builder.register_type(TypeDefPrimitive::U32.into());
+        let order = |ident: &'static str| {
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked([
+                    "bitvec".into(),
+                    ident.into(),
+                ]))
+                .composite(build::Fields::unit())
+        };
+        let lsb0_id = builder.register_type(order("Lsb0"));
+        let msb0_id = builder.register_type(order("Msb0"));
+        let old_id = builder
+            .register_type(TypeDefBitSequence::new_portable(u8_id.into(), lsb0_id.into()).into());
+        let new_id = builder
+            .register_type(TypeDefBitSequence::new_portable(u32_id.into(), msb0_id.into()).into());
+        let registry = builder.finish();
+
+        assert_eq!(
+            check_compatibility(&registry, old_id, &registry, new_id),
+            vec![
+                incompatibility(
+                    vec![],
+                    IncompatibilityKind::BitStoreChanged {
+                        old: "u8",
+                        new: "u32"
+                    }
+                ),
+                incompatibility(
+                    vec![],
+                    IncompatibilityKind::BitOrderChanged {
+                        old: "Lsb0".into(),
+                        new: "Msb0".into()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            check_compatibility(&registry, old_id, &registry, old_id),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,789 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of SCALE encoded bytes into dynamic [`Value`]s.
+//!
+//! The shape of the bytes is described only by a [`PortableRegistry`] and a type id, so no
+//! concrete Rust type is required to inspect an encoded value.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{decode::decode_value, value::Value, MetaType, PortableRegistry, Registry};
+//! # use scale::Encode;
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<(u8, bool)>()).id;
+//! let registry: PortableRegistry = registry.into();
+//!
+//! let encoded = (7u8, true).encode();
+//! let value = decode_value(&registry, id, &mut &encoded[..]).unwrap();
+//!
+//! assert_eq!(value, Value::Tuple(vec![Value::from(7u8), Value::bool(true)]));
+//! ```
+
+use crate::prelude::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    form::PortableForm,
+    size::SizeEstimator,
+    utils::{bit_order_is_lsb0, bit_store_bytes},
+    value::{Composite, Value, VariantValue},
+    Field, PortableRegistry, Type, TypeDef, TypeDefBitSequence, TypeDefPrimitive,
+};
+
+mod visitor;
+
+pub use self::visitor::{
+    decode_with_visitor, decode_with_visitor_with_depth_limit, BitSequenceRef, PrimitiveRef, Visit,
+    Visitor,
+};
+
+/// An error that may be encountered when decoding a [`Value`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The type id could not be found in the registry.
+    TypeNotFound(u32),
+    /// The input ended before the value was fully decoded.
+    NotEnoughInput {
+        /// The number of bytes required.
+        needed: usize,
+        /// The number of bytes left in the input.
+        remaining: usize,
+    },
+    /// The variant type has no variant with the encoded index.
+    InvalidVariantIndex {
+        /// The id of the variant type.
+        type_id: u32,
+        /// The encoded variant index.
+        index: u8,
+    },
+    /// A `bool` was encoded as a byte other than `0` or `1`.
+    InvalidBool(u8),
+    /// A `char` was encoded as an invalid unicode scalar value.
+    InvalidChar(u32),
+    /// A `str` was not valid UTF-8.
+    InvalidUtf8,
+    /// A compact encoded integer was malformed, or out of range for its type.
+    InvalidCompact,
+    /// The type with the given id can not be compact encoded.
+    UnsupportedCompactType(u32),
+    /// The bit store type with the given id is not one of `u8`, `u16`, `u32` or `u64`.
+    UnsupportedBitStoreType(u32),
+    /// The bit order type with the given id is neither `Lsb0` nor `Msb0`.
+    UnsupportedBitOrderType(u32),
+    /// The value is nested deeper than the given limit.
+    DepthLimitExceeded(u32),
+    /// The value holds more than the given number of items of types which may be encoded in
+    /// zero bytes.
+    TooManyItems(usize),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeNotFound(id) => write!(f, "type with id {id} not found in the registry"),
+            Self::NotEnoughInput { needed, remaining } => write!(
+                f,
+                "not enough input: needed {needed} bytes but only {remaining} remaining"
+            ),
+            Self::InvalidVariantIndex { type_id, index } => {
+                write!(
+                    f,
+                    "type with id {type_id} has no variant with index {index}"
+                )
+            }
+            Self::InvalidBool(byte) => write!(f, "invalid bool byte {byte}"),
+            Self::InvalidChar(c) => write!(f, "invalid char {c:#x}"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8 in str"),
+            Self::InvalidCompact => write!(f, "invalid compact encoded integer"),
+            Self::UnsupportedCompactType(id) => {
+                write!(f, "type with id {id} can not be compact encoded")
+            }
+            Self::UnsupportedBitStoreType(id) => {
+                write!(f, "type with id {id} is not a supported bit store type")
+            }
+            Self::UnsupportedBitOrderType(id) => {
+                write!(f, "type with id {id} is not a supported bit order type")
+            }
+            Self::DepthLimitExceeded(limit) => {
+                write!(f, "value nested deeper than the limit of {limit}")
+            }
+            Self::TooManyItems(limit) => {
+                write!(
+                    f,
+                    "value holds more than {limit} items encoded in zero bytes"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// The nesting depth up to which [`decode_value`] and [`decode_with_visitor`] decode values.
+pub const DEFAULT_DEPTH_LIMIT: u32 = 256;
+
+/// The number of sequence and array items of types which may be encoded in zero bytes, e.g.
+/// `()`, up to which a value is decoded or visited. Other items must fit in the remaining input,
+/// so that a short untrusted input can't claim billions of items.
+pub const MAX_ZERO_SIZED_ITEMS: usize = 1 << 20;
+
+/// Decodes a [`Value`] of the type with the given id from the input.
+///
+/// The input is advanced past the decoded bytes; any trailing bytes are left in place.
+///
+/// Values nested deeper than [`DEFAULT_DEPTH_LIMIT`] are rejected, so that decoding untrusted
+/// input can't overflow the stack.
+pub fn decode_value(
+    registry: &PortableRegistry,
+    type_id: u32,
+    input: &mut &[u8],
+) -> Result<Value, DecodeError> {
+    decode_value_with_depth_limit(registry, type_id, input, DEFAULT_DEPTH_LIMIT)
+}
+
+/// Decodes a [`Value`] like [`decode_value`], rejecting values nested deeper than the given
+/// limit with [`DecodeError::DepthLimitExceeded`].
+///
+/// Every composite, variant, sequence, array, tuple and compact counts as one level.
+pub fn decode_value_with_depth_limit(
+    registry: &PortableRegistry,
+    type_id: u32,
+    input: &mut &[u8],
+    depth_limit: u32,
+) -> Result<Value, DecodeError> {
+    Decoder {
+        registry,
+        depth_limit,
+        items: ItemLimits::new(registry),
+    }
+    .decode_value(type_id, input, 0)
+}
+
+/// The limits on the number of sequence and array items of a value being decoded.
+struct ItemLimits<'a> {
+    registry: &'a PortableRegistry,
+    /// The minimum encoded size of item types, computed once the first items are decoded and
+    /// only visiting the types they refer to.
+    sizes: Option<SizeEstimator<'a>>,
+    /// The number of items encoded in zero bytes which may still be decoded.
+    zero_sized_items: usize,
+}
+
+impl<'a> ItemLimits<'a> {
+    fn new(registry: &'a PortableRegistry) -> Self {
+        Self {
+            registry,
+            sizes: None,
+            zero_sized_items: MAX_ZERO_SIZED_ITEMS,
+        }
+    }
+
+    /// Checks that `len` items of the given type can be decoded from the input: items encoded in
+    /// at least one byte must fit in the remaining input, and the others count towards
+    /// [`MAX_ZERO_SIZED_ITEMS`].
+    fn check_len(&mut self, type_id: u32, len: usize, input: &[u8]) -> Result<(), DecodeError> {
+        if len == 0 {
+            return Ok(());
+        }
+        let registry = self.registry;
+        let min = self
+            .sizes
+            .get_or_insert_with(|| SizeEstimator::new(registry))
+            .bounds(type_id)
+            .map_or(0, |bounds| bounds.min);
+        if min > 0 {
+            let needed = len.saturating_mul(min);
+            if needed > input.len() {
+                return Err(DecodeError::NotEnoughInput {
+                    needed,
+                    remaining: input.len(),
+                });
+            }
+        } else {
+            self.zero_sized_items = self
+                .zero_sized_items
+                .checked_sub(len)
+                .ok_or(DecodeError::TooManyItems(MAX_ZERO_SIZED_ITEMS))?;
+        }
+        Ok(())
+    }
+}
+
+/// The registry and limits of a value being decoded.
+struct Decoder<'a> {
+    registry: &'a PortableRegistry,
+    depth_limit: u32,
+    items: ItemLimits<'a>,
+}
+
+impl Decoder<'_> {
+    fn decode_value(
+        &mut self,
+        type_id: u32,
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<Value, DecodeError> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit && !matches!(ty.type_def, TypeDef::Primitive(_)) {
+            return Err(DecodeError::DepthLimitExceeded(self.depth_limit));
+        }
+        let depth = depth + 1;
+        match &ty.type_def {
+            TypeDef::Composite(composite) => self
+                .decode_fields(&composite.fields, input, depth)
+                .map(Value::Composite),
+            TypeDef::Variant(variant) => {
+                let index = take_byte(input)?;
+                let variant = variant
+                    .variants
+                    .iter()
+                    .find(|v| v.index == index)
+                    .ok_or(DecodeError::InvalidVariantIndex { type_id, index })?;
+                let fields = self.decode_fields(&variant.fields, input, depth)?;
+                Ok(Value::Variant(VariantValue {
+                    name: to_string(&variant.name),
+                    index,
+                    fields,
+                }))
+            }
+            TypeDef::Sequence(sequence) => {
+                let len = decode_len(input)?;
+                self.decode_items(sequence.type_param.id, len, input, depth)
+                    .map(Value::Sequence)
+            }
+            TypeDef::Array(array) => self
+                .decode_items(array.type_param.id, array.len as usize, input, depth)
+                .map(Value::Array),
+            TypeDef::Tuple(tuple) => tuple
+                .fields
+                .iter()
+                .map(|ty| self.decode_value(ty.id, input, depth))
+                .collect::<Result<_, _>>()
+                .map(Value::Tuple),
+            TypeDef::Primitive(primitive) => {
+                decode_primitive_ref(primitive, input).map(|p| Value::Primitive(p.into()))
+            }
+            TypeDef::Compact(compact) => self
+                .decode_compact(compact.type_param.id, input, depth)
+                .map(Value::compact),
+            TypeDef::BitSequence(bit_sequence) => decode_bits(self.registry, bit_sequence, input)
+                .map(|bits| Value::BitSequence(bits.collect())),
+        }
+    }
+
+    fn decode_fields(
+        &mut self,
+        fields: &[Field<PortableForm>],
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<Composite, DecodeError> {
+        if fields.iter().all(|f| f.name.is_some()) && !fields.is_empty() {
+            fields
+                .iter()
+                .map(|f| {
+                    let name = f.name.as_ref().map(to_string).unwrap_or_default();
+                    Ok((name, self.decode_value(f.ty.id, input, depth)?))
+                })
+                .collect::<Result<_, _>>()
+                .map(Composite::Named)
+        } else {
+            fields
+                .iter()
+                .map(|f| self.decode_value(f.ty.id, input, depth))
+                .collect::<Result<_, _>>()
+                .map(Composite::Unnamed)
+        }
+    }
+
+    fn decode_items(
+        &mut self,
+        type_id: u32,
+        len: usize,
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<Vec<Value>, DecodeError> {
+        self.items.check_len(type_id, len, input)?;
+        // Every item occupies at least one byte for most types, so cap the up front allocation
+        // by the remaining input to avoid allocating huge vectors for bogus lengths.
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(self.decode_value(type_id, input, depth)?);
+        }
+        Ok(items)
+    }
+
+    /// Decodes the value wrapped in a `Compact`, which is either an unsigned integer or a
+    /// composite or tuple with a single field wrapping one (e.g. types implementing
+    /// [`CompactAs`](scale::CompactAs)).
+    fn decode_compact(
+        &mut self,
+        type_id: u32,
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<Value, DecodeError> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit {
+            return Err(DecodeError::DepthLimitExceeded(self.depth_limit));
+        }
+        let depth = depth + 1;
+        match &ty.type_def {
+            TypeDef::Primitive(primitive) => {
+                let n = decode_compact_int(input)?;
+                compact_primitive(type_id, primitive, n).map(|p| Value::Primitive(p.into()))
+            }
+            TypeDef::Composite(composite) => match &composite.fields[..] {
+                [] => Ok(Value::Composite(Composite::unit())),
+                [field] => {
+                    let value = self.decode_compact(field.ty.id, input, depth)?;
+                    Ok(Value::Composite(match &field.name {
+                        Some(name) => {
+                            Composite::Named(crate::prelude::vec![(to_string(name), value)])
+                        }
+                        None => Composite::Unnamed(crate::prelude::vec![value]),
+                    }))
+                }
+                _ => Err(DecodeError::UnsupportedCompactType(type_id)),
+            },
+            TypeDef::Tuple(tuple) => match &tuple.fields[..] {
+                [] => Ok(Value::Tuple(Vec::new())),
+                [field] => {
+                    let value = self.decode_compact(field.id, input, depth)?;
+                    Ok(Value::Tuple(crate::prelude::vec![value]))
+                }
+                _ => Err(DecodeError::UnsupportedCompactType(type_id)),
+            },
+            _ => Err(DecodeError::UnsupportedCompactType(type_id)),
+        }
+    }
+}
+
+pub(crate) fn resolve(
+    registry: &PortableRegistry,
+    type_id: u32,
+) -> Result<&Type<PortableForm>, DecodeError> {
+    registry
+        .resolve(type_id)
+        .ok_or(DecodeError::TypeNotFound(type_id))
+}
+
+pub(super) fn decode_primitive_ref<'a>(
+    primitive: &TypeDefPrimitive,
+    input: &mut &'a [u8],
+) -> Result<PrimitiveRef<'a>, DecodeError> {
+    let primitive = match primitive {
+        TypeDefPrimitive::Bool => match take_byte(input)? {
+            0 => PrimitiveRef::Bool(false),
+            1 => PrimitiveRef::Bool(true),
+            b => return Err(DecodeError::InvalidBool(b)),
+        },
+        TypeDefPrimitive::Char => {
+            let c = u32::from_le_bytes(take_array(input)?);
+            PrimitiveRef::Char(char::from_u32(c).ok_or(DecodeError::InvalidChar(c))?)
+        }
+        TypeDefPrimitive::Str => {
+            let len = decode_len(input)?;
+            let bytes = take(input, len)?;
+            PrimitiveRef::Str(core::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?)
+        }
+        TypeDefPrimitive::U8 => PrimitiveRef::U8(take_byte(input)?),
+        TypeDefPrimitive::U16 => PrimitiveRef::U16(u16::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::U32 => PrimitiveRef::U32(u32::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::U64 => PrimitiveRef::U64(u64::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::U128 => PrimitiveRef::U128(u128::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::U256 => PrimitiveRef::U256(take_array_ref(input)?),
+        TypeDefPrimitive::I8 => PrimitiveRef::I8(i8::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::I16 => PrimitiveRef::I16(i16::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::I32 => PrimitiveRef::I32(i32::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::I64 => PrimitiveRef::I64(i64::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::I128 => PrimitiveRef::I128(i128::from_le_bytes(take_array(input)?)),
+        TypeDefPrimitive::I256 => PrimitiveRef::I256(take_array_ref(input)?),
+    };
+    Ok(primitive)
+}
+
+/// Converts a decoded compact integer into the unsigned primitive of the type with the given
+/// id, checking that it is in range.
+pub(super) fn compact_primitive(
+    type_id: u32,
+    primitive: &TypeDefPrimitive,
+    n: u128,
+) -> Result<PrimitiveRef<'static>, DecodeError> {
+    let primitive = match primitive {
+        TypeDefPrimitive::U8 => u8::try_from(n).ok().map(PrimitiveRef::U8),
+        TypeDefPrimitive::U16 => u16::try_from(n).ok().map(PrimitiveRef::U16),
+        TypeDefPrimitive::U32 => u32::try_from(n).ok().map(PrimitiveRef::U32),
+        TypeDefPrimitive::U64 => u64::try_from(n).ok().map(PrimitiveRef::U64),
+        TypeDefPrimitive::U128 => Some(PrimitiveRef::U128(n)),
+        _ => return Err(DecodeError::UnsupportedCompactType(type_id)),
+    };
+    primitive.ok_or(DecodeError::InvalidCompact)
+}
+
+/// Decodes a bit sequence, borrowing the bit store elements from the input.
+pub(super) fn decode_bits<'a>(
+    registry: &PortableRegistry,
+    bit_sequence: &TypeDefBitSequence<PortableForm>,
+    input: &mut &'a [u8],
+) -> Result<BitSequenceRef<'a>, DecodeError> {
+    let (store_bytes, lsb0) = bit_sequence_format(registry, bit_sequence)?;
+    let len = decode_len(input)?;
+    let store_bits = store_bytes * 8;
+    // Rounded up without overflowing for lengths close to `usize::MAX`.
+    let elements = len / store_bits + usize::from(len % store_bits != 0);
+    let bytes = take(input, elements * store_bytes)?;
+    Ok(BitSequenceRef::new(bytes, len, store_bytes, lsb0))
+}
+
+/// Returns the number of bytes of the bit store type, and whether the bit order is `Lsb0`.
+fn bit_sequence_format(
+    registry: &PortableRegistry,
+    bit_sequence: &TypeDefBitSequence<PortableForm>,
+) -> Result<(usize, bool), DecodeError> {
+    let store_id = bit_sequence.bit_store_type.id;
+    let store_bytes = bit_store_bytes(resolve(registry, store_id)?)
+        .ok_or(DecodeError::UnsupportedBitStoreType(store_id))?;
+    let order_id = bit_sequence.bit_order_type.id;
+    let lsb0 = bit_order_is_lsb0(resolve(registry, order_id)?)
+        .ok_or(DecodeError::UnsupportedBitOrderType(order_id))?;
+    Ok((store_bytes, lsb0))
+}
+
+fn to_string<S: AsRef<str>>(s: &S) -> String {
+    s.as_ref().to_string()
+}
+
+pub(super) fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if input.len() < len {
+        return Err(DecodeError::NotEnoughInput {
+            needed: len,
+            remaining: input.len(),
+        });
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+pub(super) fn take_byte(input: &mut &[u8]) -> Result<u8, DecodeError> {
+    take(input, 1).map(|bytes| bytes[0])
+}
+
+fn take_array<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    take_array_ref(input).copied()
+}
+
+fn take_array_ref<'a, const N: usize>(input: &mut &'a [u8]) -> Result<&'a [u8; N], DecodeError> {
+    Ok(take(input, N)?
+        .try_into()
+        .expect("`take` returns exactly `N` bytes; qed"))
+}
+
+/// Decodes a compact encoded length prefix.
+pub(super) fn decode_len(input: &mut &[u8]) -> Result<usize, DecodeError> {
+    u32::try_from(decode_compact_int(input)?)
+        .map(|len| len as usize)
+        .map_err(|_| DecodeError::InvalidCompact)
+}
+
+/// Decodes a compact encoded unsigned integer of up to 128 bits, rejecting non-canonical
+/// encodings in the same way as `parity-scale-codec`.
+pub(super) fn decode_compact_int(input: &mut &[u8]) -> Result<u128, DecodeError> {
+    let prefix = take_byte(input)?;
+    let (n, min) = match prefix & 0b11 {
+        0b00 => return Ok(u128::from(prefix >> 2)),
+        0b01 => {
+            let n = u16::from_le_bytes([prefix, take_byte(input)?]) >> 2;
+            (u128::from(n), 1 << 6)
+        }
+        0b10 => {
+            let mut bytes = [prefix, 0, 0, 0];
+            bytes[1..].copy_from_slice(take(input, 3)?);
+            (u128::from(u32::from_le_bytes(bytes) >> 2), 1 << 14)
+        }
+        _ => {
+            let len = (prefix >> 2) as usize + 4;
+            if len > 16 {
+                return Err(DecodeError::InvalidCompact);
+            }
+            let mut bytes = [0u8; 16];
+            bytes[..len].copy_from_slice(take(input, len)?);
+            if bytes[len - 1] == 0 {
+                return Err(DecodeError::InvalidCompact);
+            }
+            (u128::from_le_bytes(bytes), 1 << 30)
+        }
+    };
+    if n < min {
+        return Err(DecodeError::InvalidCompact);
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale::{Compact, Encode};
+    use scale_info_derive::TypeInfo;
+
+    fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        (id, registry.into())
+    }
+
+    fn decode<T: TypeInfo + Encode + 'static>(value: T) -> Value {
+        let (id, registry) = registry_with::<T>();
+        let encoded = value.encode();
+        let input = &mut &encoded[..];
+        let decoded = decode_value(&registry, id, input).expect("value should decode");
+        assert!(input.is_empty(), "all input should be consumed");
+        decoded
+    }
+
+    #[test]
+    fn decode_primitives() {
+        assert_eq!(decode(true), Value::bool(true));
+        assert_eq!(decode(String::from("hello")), Value::string("hello"));
+        assert_eq!(decode(u128::MAX), Value::from(u128::MAX));
+        assert_eq!(decode(-5i16), Value::from(-5i16));
+    }
+
+    #[test]
+    fn decode_composites_and_variants() {
+        #[allow(unused)]
+        #[derive(TypeInfo, Encode)]
+        struct Named {
+            a: u8,
+            b: Option<(bool, u32)>,
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo, Encode)]
+        enum E {
+            #[codec(index = 5)]
+            A(u16),
+            B {
+                x: Vec<u8>,
+            },
+        }
+
+        assert_eq!(
+            decode(Named {
+                a: 1,
+                b: Some((true, 2))
+            }),
+            Value::named_composite([
+                ("a", Value::from(1u8)),
+                (
+                    "b",
+                    Value::Variant(VariantValue {
+                        name: "Some".into(),
+                        index: 1,
+                        fields: Composite::Unnamed(vec![Value::Tuple(vec![
+                            Value::bool(true),
+                            Value::from(2u32)
+                        ])]),
+                    })
+                ),
+            ])
+        );
+        assert_eq!(
+            decode(E::A(3)),
+            Value::Variant(VariantValue {
+                name: "A".into(),
+                index: 5,
+                fields: Composite::Unnamed(vec![Value::from(3u16)]),
+            })
+        );
+        assert_eq!(
+            decode(E::B { x: vec![1, 2] }),
+            Value::Variant(VariantValue {
+                name: "B".into(),
+                index: 1,
+                fields: Composite::Named(vec![(
+                    "x".into(),
+                    Value::Sequence(vec![Value::from(1u8), Value::from(2u8)])
+                )]),
+            })
+        );
+    }
+
+    #[test]
+    fn decode_arrays_and_compacts() {
+        #[allow(unused)]
+        #[derive(TypeInfo, Encode)]
+        struct S {
+            #[codec(compact)]
+            a: u64,
+            b: [u8; 2],
+        }
+
+        assert_eq!(
+            decode(S {
+                a: u64::MAX,
+                b: [1, 2]
+            }),
+            Value::named_composite([
+                ("a", Value::compact(Value::from(u64::MAX))),
+                ("b", Value::Array(vec![Value::from(1u8), Value::from(2u8)])),
+            ])
+        );
+        assert_eq!(
+            decode(Compact(1_000u32)),
+            Value::compact(Value::from(1_000u32))
+        );
+    }
+
+    #[test]
+    fn decode_bit_sequence() {
+        let mut builder = crate::PortableRegistryBuilder::new();
+        let u16_id = builder.register_type(TypeDefPrimitive::U16.into());
+        let order = |ident: &'static str| {
+            Type::builder_portable()
+                .path(crate::Path::from_segments_unchecked([
+                    "bitvec".into(),
+                    "order".into(),
+                    ident.into(),
+                ]))
+                .composite(crate::build::Fields::unit())
+        };
+        let lsb0_id = builder.register_type(order("Lsb0"));
+        let msb0_id = builder.register_type(order("Msb0"));
+        let lsb0_seq = builder
+            .register_type(TypeDefBitSequence::new_portable(u16_id.into(), lsb0_id.into()).into());
+        let msb0_seq = builder
+            .register_type(TypeDefBitSequence::new_portable(u16_id.into(), msb0_id.into()).into());
+        let registry = builder.finish();
+
+        // 3 bits, stored in a single u16.
+        let encoded = [3 << 2, 0b0000_0101, 0b1000_0000];
+        let lsb0 = decode_value(&registry, lsb0_seq, &mut &encoded[..]);
+        assert_eq!(lsb0, Ok(Value::BitSequence(vec![true, false, true])));
+        let msb0 = decode_value(&registry, msb0_seq, &mut &encoded[..]);
+        assert_eq!(msb0, Ok(Value::BitSequence(vec![true, false, false])));
+
+        // The largest length, stored in 2^28 u16s.
+        let encoded = scale::Compact(u32::MAX).encode();
+        assert_eq!(
+            decode_value(&registry, lsb0_seq, &mut &encoded[..]),
+            Err(DecodeError::NotEnoughInput {
+                needed: 1 << 29,
+                remaining: 0
+            })
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        let (id, registry) = registry_with::<Option<u32>>();
+        assert_eq!(
+            decode_value(&registry, id, &mut &[2u8][..]),
+            Err(DecodeError::InvalidVariantIndex {
+                type_id: id,
+                index: 2
+            })
+        );
+        assert_eq!(
+            decode_value(&registry, id, &mut &[1u8, 0, 0][..]),
+            Err(DecodeError::NotEnoughInput {
+                needed: 4,
+                remaining: 2
+            })
+        );
+        assert_eq!(
+            decode_value(&registry, 1000, &mut &[0u8][..]),
+            Err(DecodeError::TypeNotFound(1000))
+        );
+
+        let (id, registry) = registry_with::<char>();
+        assert_eq!(
+            decode_value(&registry, id, &mut &0xD800u32.to_le_bytes()[..]),
+            Err(DecodeError::InvalidChar(0xD800))
+        );
+
+        let (id, registry) = registry_with::<Compact<u8>>();
+        let encoded = Compact(256u16).encode();
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Err(DecodeError::InvalidCompact)
+        );
+    }
+
+    #[test]
+    fn decode_depth_limit() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Tree {
+            Leaf,
+            Node(Box<Tree>),
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Loop(Box<Loop>);
+
+        let (id, registry) = registry_with::<Tree>();
+        let mut encoded = vec![1u8; 300];
+        encoded.push(0);
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Err(DecodeError::DepthLimitExceeded(DEFAULT_DEPTH_LIMIT))
+        );
+        assert!(decode_value_with_depth_limit(&registry, id, &mut &encoded[..], 301).is_ok());
+        assert_eq!(
+            decode_value_with_depth_limit(&registry, id, &mut &encoded[..], 300),
+            Err(DecodeError::DepthLimitExceeded(300))
+        );
+
+        // A composite referring to itself would recurse without consuming any input.
+        let (id, registry) = registry_with::<Loop>();
+        assert_eq!(
+            decode_value(&registry, id, &mut &[][..]),
+            Err(DecodeError::DepthLimitExceeded(DEFAULT_DEPTH_LIMIT))
+        );
+    }
+
+    #[test]
+    fn decode_lengths_are_bounded_by_the_input() {
+        let (id, registry) = registry_with::<Vec<()>>();
+        let encoded = Compact(u32::MAX).encode();
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Err(DecodeError::TooManyItems(MAX_ZERO_SIZED_ITEMS))
+        );
+        let encoded = vec![(); 3].encode();
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Ok(Value::Sequence(vec![Value::Tuple(vec![]); 3]))
+        );
+
+        let (id, registry) = registry_with::<Vec<u16>>();
+        let mut encoded = Compact(10u32).encode();
+        encoded.extend([0; 5]);
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Err(DecodeError::NotEnoughInput {
+                needed: 20,
+                remaining: 5
+            })
+        );
+    }
+}
@@ -0,0 +1,794 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming decoding of SCALE encoded bytes, driven by a [`Visitor`].
+//!
+//! Unlike [`decode_value`](super::decode_value), no value tree is materialized: the visitor is
+//! called back for every value as it is decoded, borrowing from the input where possible.
+//! Returning [`Visit::Skip`] from any of the `*_start` callbacks or from
+//! [`Visitor::visit_field`] skips over the bytes of that value without any further callbacks.
+
+use crate::{
+    form::PortableForm, utils::primitive_size, value::Primitive, Field, PortableRegistry, TypeDef,
+    TypeDefComposite, TypeDefPrimitive, Variant,
+};
+
+use super::{
+    compact_primitive, decode_bits, decode_compact_int, decode_len, decode_primitive_ref, resolve,
+    take, take_byte, DecodeError, ItemLimits, DEFAULT_DEPTH_LIMIT,
+};
+
+/// Whether to descend into a value, or skip over it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visit {
+    /// Decode the value, calling back the visitor for any nested values.
+    Enter,
+    /// Skip over the bytes of the value without calling back the visitor.
+    Skip,
+}
+
+/// Callbacks invoked while decoding SCALE encoded bytes with [`decode_with_visitor`].
+///
+/// All callbacks have default implementations which do nothing and [`Visit::Enter`] every
+/// value, so implementors only need to override the callbacks they are interested in.
+///
+/// For any `*_start` callback returning [`Visit::Enter`] the matching `*_end` callback is
+/// called once all nested values have been visited. Values inside a
+/// [`TypeDef::Compact`] are visited like any other value, between
+/// [`Visitor::visit_compact_start`] and [`Visitor::visit_compact_end`].
+#[allow(unused_variables)]
+pub trait Visitor {
+    /// The error returned by the visitor, which must be able to represent decoding errors.
+    type Error: From<DecodeError>;
+
+    /// Called for every primitive value.
+    fn visit_primitive(
+        &mut self,
+        type_id: u32,
+        value: PrimitiveRef<'_>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the fields of a composite value.
+    fn visit_composite_start(
+        &mut self,
+        type_id: u32,
+        composite: &TypeDefComposite<PortableForm>,
+    ) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the fields of a composite value.
+    fn visit_composite_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the fields of a variant value.
+    fn visit_variant_start(
+        &mut self,
+        type_id: u32,
+        variant: &Variant<PortableForm>,
+    ) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the fields of a variant value.
+    fn visit_variant_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before each field of a composite or variant value, with the position of the
+    /// field.
+    fn visit_field(
+        &mut self,
+        field: &Field<PortableForm>,
+        index: usize,
+    ) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called before the items of a sequence, with the number of items.
+    fn visit_sequence_start(&mut self, type_id: u32, len: usize) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the items of a sequence.
+    fn visit_sequence_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the items of an array, with the number of items.
+    fn visit_array_start(&mut self, type_id: u32, len: usize) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the items of an array.
+    fn visit_array_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the elements of a tuple, with the number of elements.
+    fn visit_tuple_start(&mut self, type_id: u32, len: usize) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the elements of a tuple.
+    fn visit_tuple_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the value wrapped in a `Compact`.
+    fn visit_compact_start(&mut self, type_id: u32) -> Result<Visit, Self::Error> {
+        Ok(Visit::Enter)
+    }
+
+    /// Called after the value wrapped in a `Compact`.
+    fn visit_compact_end(&mut self, type_id: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called for every bit sequence.
+    fn visit_bit_sequence(
+        &mut self,
+        type_id: u32,
+        bits: BitSequenceRef<'_>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A primitive value borrowing from the decoded input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimitiveRef<'a> {
+    /// A `bool`.
+    Bool(bool),
+    /// A `char`.
+    Char(char),
+    /// A `str`.
+    Str(&'a str),
+    /// A `u8`.
+    U8(u8),
+    /// A `u16`.
+    U16(u16),
+    /// A `u32`.
+    U32(u32),
+    /// A `u64`.
+    U64(u64),
+    /// A `u128`.
+    U128(u128),
+    /// A 256 bits unsigned int, as little endian bytes.
+    U256(&'a [u8; 32]),
+    /// An `i8`.
+    I8(i8),
+    /// An `i16`.
+    I16(i16),
+    /// An `i32`.
+    I32(i32),
+    /// An `i64`.
+    I64(i64),
+    /// An `i128`.
+    I128(i128),
+    /// A 256 bits signed int, as little endian bytes.
+    I256(&'a [u8; 32]),
+}
+
+impl From<PrimitiveRef<'_>> for Primitive {
+    fn from(primitive: PrimitiveRef<'_>) -> Self {
+        match primitive {
+            PrimitiveRef::Bool(b) => Primitive::Bool(b),
+            PrimitiveRef::Char(c) => Primitive::Char(c),
+            PrimitiveRef::Str(s) => Primitive::Str(s.into()),
+            PrimitiveRef::U8(n) => Primitive::U8(n),
+            PrimitiveRef::U16(n) => Primitive::U16(n),
+            PrimitiveRef::U32(n) => Primitive::U32(n),
+            PrimitiveRef::U64(n) => Primitive::U64(n),
+            PrimitiveRef::U128(n) => Primitive::U128(n),
+            PrimitiveRef::U256(bytes) => Primitive::U256(*bytes),
+            PrimitiveRef::I8(n) => Primitive::I8(n),
+            PrimitiveRef::I16(n) => Primitive::I16(n),
+            PrimitiveRef::I32(n) => Primitive::I32(n),
+            PrimitiveRef::I64(n) => Primitive::I64(n),
+            PrimitiveRef::I128(n) => Primitive::I128(n),
+            PrimitiveRef::I256(bytes) => Primitive::I256(*bytes),
+        }
+    }
+}
+
+/// A sequence of bits borrowing from the decoded input.
+///
+/// Iterating yields each bit in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSequenceRef<'a> {
+    bytes: &'a [u8],
+    len: usize,
+    pos: usize,
+    store_bytes: usize,
+    lsb0: bool,
+}
+
+impl<'a> BitSequenceRef<'a> {
+    pub(super) fn new(bytes: &'a [u8], len: usize, store_bytes: usize, lsb0: bool) -> Self {
+        Self {
+            bytes,
+            len,
+            pos: 0,
+            store_bytes,
+            lsb0,
+        }
+    }
+
+    /// Returns the raw bytes of the bit store elements.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+impl Iterator for BitSequenceRef<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.pos >= self.len {
+            return None;
+        }
+        let store_bits = self.store_bytes * 8;
+        let (element, i) = (self.pos / store_bits, self.pos % store_bits);
+        // Store elements are little endian, so the byte holding the bit is found by its
+        // significance within the element.
+        let significance = if self.lsb0 { i } else { store_bits - 1 - i };
+        let byte = self.bytes[element * self.store_bytes + significance / 8];
+        self.pos += 1;
+        Some((byte >> (significance % 8)) & 1 == 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.pos;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for BitSequenceRef<'_> {}
+
+/// Decodes a value of the type with the given id from the input, calling back the visitor for
+/// every value encountered.
+///
+/// The input is advanced past the decoded bytes; any trailing bytes are left in place.
+///
+/// Values nested deeper than [`DEFAULT_DEPTH_LIMIT`] are rejected, including the values which
+/// are skipped, so that decoding untrusted input can't overflow the stack.
+pub fn decode_with_visitor<V: Visitor>(
+    registry: &PortableRegistry,
+    type_id: u32,
+    input: &mut &[u8],
+    visitor: &mut V,
+) -> Result<(), V::Error> {
+    decode_with_visitor_with_depth_limit(registry, type_id, input, visitor, DEFAULT_DEPTH_LIMIT)
+}
+
+/// Decodes a value like [`decode_with_visitor`], rejecting values nested deeper than the given
+/// limit with [`DecodeError::DepthLimitExceeded`].
+///
+/// Levels are counted as for [`decode_value_with_depth_limit`](super::decode_value_with_depth_limit).
+pub fn decode_with_visitor_with_depth_limit<V: Visitor>(
+    registry: &PortableRegistry,
+    type_id: u32,
+    input: &mut &[u8],
+    visitor: &mut V,
+    depth_limit: u32,
+) -> Result<(), V::Error> {
+    Walker {
+        registry,
+        depth_limit,
+        items: ItemLimits::new(registry),
+    }
+    .visit(type_id, input, visitor, 0)
+}
+
+/// The registry and limits of a value being visited.
+struct Walker<'a> {
+    registry: &'a PortableRegistry,
+    depth_limit: u32,
+    items: ItemLimits<'a>,
+}
+
+impl Walker<'_> {
+    fn visit<V: Visitor>(
+        &mut self,
+        type_id: u32,
+        input: &mut &[u8],
+        visitor: &mut V,
+        depth: u32,
+    ) -> Result<(), V::Error> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit && !matches!(ty.type_def, TypeDef::Primitive(_)) {
+            return Err(DecodeError::DepthLimitExceeded(self.depth_limit).into());
+        }
+        let depth = depth + 1;
+        match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                if visitor.visit_composite_start(type_id, composite)? == Visit::Skip {
+                    return self
+                        .skip_fields(&composite.fields, input, depth)
+                        .map_err(Into::into);
+                }
+                self.visit_fields(&composite.fields, input, visitor, depth)?;
+                visitor.visit_composite_end(type_id)
+            }
+            TypeDef::Variant(variant) => {
+                let index = take_byte(input)?;
+                let variant = variant
+                    .variants
+                    .iter()
+                    .find(|v| v.index == index)
+                    .ok_or(DecodeError::InvalidVariantIndex { type_id, index })?;
+                if visitor.visit_variant_start(type_id, variant)? == Visit::Skip {
+                    return self
+                        .skip_fields(&variant.fields, input, depth)
+                        .map_err(Into::into);
+                }
+                self.visit_fields(&variant.fields, input, visitor, depth)?;
+                visitor.visit_variant_end(type_id)
+            }
+            TypeDef::Sequence(sequence) => {
+                let len = decode_len(input)?;
+                if visitor.visit_sequence_start(type_id, len)? == Visit::Skip {
+                    return self
+                        .skip_items(sequence.type_param.id, len, input, depth)
+                        .map_err(Into::into);
+                }
+                self.items.check_len(sequence.type_param.id, len, input)?;
+                for _ in 0..len {
+                    self.visit(sequence.type_param.id, input, visitor, depth)?;
+                }
+                visitor.visit_sequence_end(type_id)
+            }
+            TypeDef::Array(array) => {
+                let len = array.len as usize;
+                if visitor.visit_array_start(type_id, len)? == Visit::Skip {
+                    return self
+                        .skip_items(array.type_param.id, len, input, depth)
+                        .map_err(Into::into);
+                }
+                self.items.check_len(array.type_param.id, len, input)?;
+                for _ in 0..len {
+                    self.visit(array.type_param.id, input, visitor, depth)?;
+                }
+                visitor.visit_array_end(type_id)
+            }
+            TypeDef::Tuple(tuple) => {
+                if visitor.visit_tuple_start(type_id, tuple.fields.len())? == Visit::Skip {
+                    for ty in &tuple.fields {
+                        self.skip(ty.id, input, depth)?;
+                    }
+                    return Ok(());
+                }
+                for ty in &tuple.fields {
+                    self.visit(ty.id, input, visitor, depth)?;
+                }
+                visitor.visit_tuple_end(type_id)
+            }
+            TypeDef::Primitive(primitive) => {
+                let value = decode_primitive_ref(primitive, input)?;
+                visitor.visit_primitive(type_id, value)
+            }
+            TypeDef::Compact(compact) => {
+                if visitor.visit_compact_start(type_id)? == Visit::Skip {
+                    return self
+                        .visit_compact(compact.type_param.id, input, &mut Skipper, depth)
+                        .map_err(Into::into);
+                }
+                self.visit_compact(compact.type_param.id, input, visitor, depth)?;
+                visitor.visit_compact_end(type_id)
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                let bits = decode_bits(self.registry, bit_sequence, input)?;
+                visitor.visit_bit_sequence(type_id, bits)
+            }
+        }
+    }
+
+    fn visit_fields<V: Visitor>(
+        &mut self,
+        fields: &[Field<PortableForm>],
+        input: &mut &[u8],
+        visitor: &mut V,
+        depth: u32,
+    ) -> Result<(), V::Error> {
+        for (index, field) in fields.iter().enumerate() {
+            match visitor.visit_field(field, index)? {
+                Visit::Enter => self.visit(field.ty.id, input, visitor, depth)?,
+                Visit::Skip => self.skip(field.ty.id, input, depth)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Visits the value wrapped in a `Compact`, which is either an unsigned integer or a
+    /// composite or tuple with a single field wrapping one.
+    fn visit_compact<V: Visitor>(
+        &mut self,
+        type_id: u32,
+        input: &mut &[u8],
+        visitor: &mut V,
+        depth: u32,
+    ) -> Result<(), V::Error> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit {
+            return Err(DecodeError::DepthLimitExceeded(self.depth_limit).into());
+        }
+        let depth = depth + 1;
+        match &ty.type_def {
+            TypeDef::Primitive(primitive) => {
+                let n = decode_compact_int(input)?;
+                let value = compact_primitive(type_id, primitive, n)?;
+                visitor.visit_primitive(type_id, value)
+            }
+            TypeDef::Composite(composite) if composite.fields.len() <= 1 => {
+                if visitor.visit_composite_start(type_id, composite)? == Visit::Enter {
+                    if let Some(field) = composite.fields.first() {
+                        match visitor.visit_field(field, 0)? {
+                            Visit::Enter => {
+                                self.visit_compact(field.ty.id, input, visitor, depth)?
+                            }
+                            Visit::Skip => {
+                                self.visit_compact(field.ty.id, input, &mut Skipper, depth)?
+                            }
+                        }
+                    }
+                    visitor.visit_composite_end(type_id)
+                } else if let Some(field) = composite.fields.first() {
+                    self.visit_compact(field.ty.id, input, &mut Skipper, depth)
+                        .map_err(Into::into)
+                } else {
+                    Ok(())
+                }
+            }
+            TypeDef::Tuple(tuple) if tuple.fields.len() <= 1 => {
+                if visitor.visit_tuple_start(type_id, tuple.fields.len())? == Visit::Enter {
+                    if let Some(field) = tuple.fields.first() {
+                        self.visit_compact(field.id, input, visitor, depth)?;
+                    }
+                    visitor.visit_tuple_end(type_id)
+                } else if let Some(field) = tuple.fields.first() {
+                    self.visit_compact(field.id, input, &mut Skipper, depth)
+                        .map_err(Into::into)
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Err(DecodeError::UnsupportedCompactType(type_id).into()),
+        }
+    }
+
+    /// Skips over a value nested at the given depth.
+    fn skip(&mut self, type_id: u32, input: &mut &[u8], depth: u32) -> Result<(), DecodeError> {
+        // Fixed size primitives can be skipped without decoding them.
+        if let TypeDef::Primitive(primitive) = &resolve(self.registry, type_id)?.type_def {
+            if let Some(size) = skipped_size(primitive) {
+                return take(input, size).map(|_| ());
+            }
+        }
+        self.visit(type_id, input, &mut Skipper, depth)
+    }
+
+    fn skip_fields(
+        &mut self,
+        fields: &[Field<PortableForm>],
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<(), DecodeError> {
+        for field in fields {
+            self.skip(field.ty.id, input, depth)?;
+        }
+        Ok(())
+    }
+
+    fn skip_items(
+        &mut self,
+        type_id: u32,
+        len: usize,
+        input: &mut &[u8],
+        depth: u32,
+    ) -> Result<(), DecodeError> {
+        if let TypeDef::Primitive(primitive) = &resolve(self.registry, type_id)?.type_def {
+            if let Some(size) = skipped_size(primitive) {
+                let total = size.checked_mul(len).ok_or(DecodeError::NotEnoughInput {
+                    needed: usize::MAX,
+                    remaining: input.len(),
+                })?;
+                return take(input, total).map(|_| ());
+            }
+        }
+        self.items.check_len(type_id, len, input)?;
+        for _ in 0..len {
+            self.skip(type_id, input, depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// A visitor which visits nothing, used to skip over values.
+struct Skipper;
+
+impl Visitor for Skipper {
+    type Error = DecodeError;
+}
+
+/// Returns the encoded size of the primitives which can be skipped without decoding them, i.e.
+/// of the fixed size primitives other than `bool` and `char`, whose values have to be validated.
+fn skipped_size(primitive: &TypeDefPrimitive) -> Option<usize> {
+    match primitive {
+        TypeDefPrimitive::Bool | TypeDefPrimitive::Char => None,
+        _ => primitive_size(primitive),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decode::MAX_ZERO_SIZED_ITEMS,
+        prelude::{string::String, vec, vec::Vec},
+        *,
+    };
+    use scale::{Compact, Encode};
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Encode)]
+    struct Block {
+        number: u32,
+        extrinsics: Vec<Extrinsic>,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Encode)]
+    struct Extrinsic {
+        signer: Option<[u8; 4]>,
+        call: Vec<u8>,
+        #[codec(compact)]
+        tip: u64,
+    }
+
+    fn block() -> (u32, PortableRegistry, Vec<u8>) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<Block>()).id;
+        let block = Block {
+            number: 42,
+            extrinsics: vec![
+                Extrinsic {
+                    signer: Some([1, 2, 3, 4]),
+                    call: vec![9; 100],
+                    tip: 7,
+                },
+                Extrinsic {
+                    signer: None,
+                    call: vec![],
+                    tip: 1_000_000,
+                },
+            ],
+        };
+        (id, registry.into(), block.encode())
+    }
+
+    /// Collects the tips of all extrinsics, skipping everything else.
+    #[derive(Default)]
+    struct Tips {
+        in_tip: bool,
+        tips: Vec<u64>,
+        events: usize,
+    }
+
+    impl Visitor for Tips {
+        type Error = DecodeError;
+
+        fn visit_field(
+            &mut self,
+            field: &Field<PortableForm>,
+            _index: usize,
+        ) -> Result<Visit, Self::Error> {
+            self.events += 1;
+            Ok(match field.name.as_deref() {
+                Some("number") | Some("signer") | Some("call") => Visit::Skip,
+                name => {
+                    self.in_tip = name == Some("tip");
+                    Visit::Enter
+                }
+            })
+        }
+
+        fn visit_primitive(
+            &mut self,
+            _type_id: u32,
+            value: PrimitiveRef<'_>,
+        ) -> Result<(), Self::Error> {
+            self.events += 1;
+            if let (true, PrimitiveRef::U64(tip)) = (self.in_tip, value) {
+                self.tips.push(tip);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn visitor_projects_fields() {
+        let (id, registry, encoded) = block();
+        let mut input = &encoded[..];
+        let mut tips = Tips::default();
+        decode_with_visitor(&registry, id, &mut input, &mut tips).unwrap();
+
+        assert!(input.is_empty());
+        assert_eq!(tips.tips, vec![7, 1_000_000]);
+        // 2 top level fields, 3 fields and 1 tip per extrinsic.
+        assert_eq!(tips.events, 2 + 2 * 4);
+    }
+
+    /// Renders all values it visits, to check the order of callbacks.
+    #[derive(Default)]
+    struct Render(String);
+
+    impl Visitor for Render {
+        type Error = DecodeError;
+
+        fn visit_primitive(
+            &mut self,
+            _type_id: u32,
+            value: PrimitiveRef<'_>,
+        ) -> Result<(), Self::Error> {
+            self.0 += &format!("{value:?} ");
+            Ok(())
+        }
+
+        fn visit_variant_start(
+            &mut self,
+            _type_id: u32,
+            variant: &Variant<PortableForm>,
+        ) -> Result<Visit, Self::Error> {
+            self.0 += &format!("{}( ", variant.name);
+            Ok(Visit::Enter)
+        }
+
+        fn visit_variant_end(&mut self, _type_id: u32) -> Result<(), Self::Error> {
+            self.0 += ") ";
+            Ok(())
+        }
+
+        fn visit_sequence_start(
+            &mut self,
+            _type_id: u32,
+            len: usize,
+        ) -> Result<Visit, Self::Error> {
+            self.0 += &format!("[{len}] ");
+            Ok(if len > 10 { Visit::Skip } else { Visit::Enter })
+        }
+
+        fn visit_compact_start(&mut self, _type_id: u32) -> Result<Visit, Self::Error> {
+            self.0 += "compact ";
+            Ok(Visit::Enter)
+        }
+    }
+
+    #[test]
+    fn visitor_callback_order() {
+        let (id, registry, encoded) = block();
+        let mut render = Render::default();
+        decode_with_visitor(&registry, id, &mut &encoded[..], &mut render).unwrap();
+        assert_eq!(
+            render.0,
+            "U32(42) [2] \
+             Some( U8(1) U8(2) U8(3) U8(4) ) [100] compact U64(7) \
+             None( ) [0] compact U64(1000000) "
+        );
+    }
+
+    #[test]
+    fn visitor_reports_short_input() {
+        let (id, registry, encoded) = block();
+        let mut render = Render::default();
+        let truncated = &encoded[..encoded.len() - 1];
+        assert!(matches!(
+            decode_with_visitor(&registry, id, &mut &truncated[..], &mut render),
+            Err(DecodeError::NotEnoughInput { .. })
+        ));
+    }
+
+    #[test]
+    fn visitor_depth_limit() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Loop(Box<Loop>);
+
+        #[allow(unused)]
+        #[derive(TypeInfo, Encode)]
+        enum Tree {
+            Leaf,
+            Node(Box<Tree>),
+        }
+
+        // A composite referring to itself would recurse without consuming any input.
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<Loop>()).id;
+        let registry: PortableRegistry = registry.into();
+        assert_eq!(
+            decode_with_visitor(&registry, id, &mut &[][..], &mut Render::default()),
+            Err(DecodeError::DepthLimitExceeded(DEFAULT_DEPTH_LIMIT))
+        );
+        // Skipped values are nested all the same.
+        assert_eq!(
+            decode_with_visitor(&registry, id, &mut &[][..], &mut Skipper),
+            Err(DecodeError::DepthLimitExceeded(DEFAULT_DEPTH_LIMIT))
+        );
+
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<Tree>()).id;
+        let registry: PortableRegistry = registry.into();
+        let mut encoded = vec![1u8; 10];
+        encoded.push(0);
+        let decode = |limit| {
+            decode_with_visitor_with_depth_limit(
+                &registry,
+                id,
+                &mut &encoded[..],
+                &mut Render::default(),
+                limit,
+            )
+        };
+        assert_eq!(decode(11), Ok(()));
+        assert_eq!(decode(10), Err(DecodeError::DepthLimitExceeded(10)));
+    }
+
+    #[test]
+    fn visitor_lengths_are_bounded_by_the_input() {
+        /// Skips over every sequence.
+        struct SkipSequences;
+
+        impl Visitor for SkipSequences {
+            type Error = DecodeError;
+
+            fn visit_sequence_start(&mut self, _: u32, _: usize) -> Result<Visit, DecodeError> {
+                Ok(Visit::Skip)
+            }
+        }
+
+        let mut registry = Registry::new();
+        let unit = registry.register_type(&MetaType::new::<Vec<()>>()).id;
+        let bools = registry.register_type(&MetaType::new::<Vec<bool>>()).id;
+        let registry: PortableRegistry = registry.into();
+
+        let encoded = Compact(u32::MAX).encode();
+        assert_eq!(
+            decode_with_visitor(&registry, unit, &mut &encoded[..], &mut Render::default()),
+            Err(DecodeError::TooManyItems(MAX_ZERO_SIZED_ITEMS))
+        );
+        assert_eq!(
+            decode_with_visitor(&registry, unit, &mut &encoded[..], &mut SkipSequences),
+            Err(DecodeError::TooManyItems(MAX_ZERO_SIZED_ITEMS))
+        );
+
+        let mut encoded = Compact(10u32).encode();
+        encoded.extend([0; 5]);
+        let short = Err(DecodeError::NotEnoughInput {
+            needed: 10,
+            remaining: 5,
+        });
+        assert_eq!(
+            decode_with_visitor(&registry, bools, &mut &encoded[..], &mut Render::default()),
+            short
+        );
+        assert_eq!(
+            decode_with_visitor(&registry, bools, &mut &encoded[..], &mut SkipSequences),
+            short
+        );
+    }
+}
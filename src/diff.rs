@@ -0,0 +1,872 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human readable differences between two registries.
+//!
+//! Type ids are not stable between registries, so types are matched by their
+//! [`Path`](crate::Path) instead. Types without a path, e.g. primitives, tuples and sequences,
+//! are only compared as part of the fields referring to them, through their rendering with
+//! [`PortableRegistry::display_type`] and through the type names of the fields. When
+//! several types share a path, e.g. different instantiations of a generic type, they are matched
+//! by their type parameters and reported by their path followed by their type parameters, e.g.
+//! `Wrapper<u8>`. A path with a single instantiation in each registry is compared as is, so that
+//! changes to its type parameters are reported.
+//!
+//! Unlike the [`compat`](crate::compat) module, every change is reported, including changes to
+//! docs and names which do not affect the encoding.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{diff::diff_registries, MetaType, PortableRegistry, Registry, TypeInfo};
+//! fn registry_with<T: TypeInfo + 'static>() -> PortableRegistry {
+//!     let mut registry = Registry::new();
+//!     registry.register_type(&MetaType::new::<T>());
+//!     registry.into()
+//! }
+//!
+//! let diff = diff_registries(&registry_with::<Option<u8>>(), &registry_with::<Result<u8, ()>>());
+//! assert_eq!(diff.removed_types, vec!["Option"]);
+//! assert_eq!(diff.added_types, vec!["Result"]);
+//! assert_eq!(diff.to_string(), "+ Result\n- Option\n");
+//! ```
+
+use crate::prelude::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    form::PortableForm, utils::type_def_kind, Field, PortableRegistry, Type, TypeDef, Variant,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Returns the differences between the types of the `old` and `new` registries.
+pub fn diff_registries(old: &PortableRegistry, new: &PortableRegistry) -> RegistryDiff {
+    let old_types = types_by_path(old);
+    let new_types = types_by_path(new);
+
+    let mut diff = RegistryDiff::default();
+    for (path, old_instances) in &old_types {
+        let new_instances = match new_types.get(path) {
+            Some(new_instances) => new_instances,
+            None => {
+                diff.removed_types.push(path.clone());
+                continue;
+            }
+        };
+        if let (Some(old_ty), Some(new_ty)) = (single(old_instances), single(new_instances)) {
+            diff.push_changes(path.clone(), diff_types((old, old_ty), (new, new_ty)));
+            continue;
+        }
+        for (name, old_ty) in old_instances {
+            match new_instances.get(name) {
+                Some(new_ty) => {
+                    diff.push_changes(name.clone(), diff_types((old, old_ty), (new, new_ty)))
+                }
+                None => diff.removed_types.push(name.clone()),
+            }
+        }
+    }
+    for (path, new_instances) in &new_types {
+        match old_types.get(path) {
+            Some(old_instances) => {
+                if single(old_instances).is_none() || single(new_instances).is_none() {
+                    diff.added_types.extend(
+                        new_instances
+                            .keys()
+                            .filter(|name| !old_instances.contains_key(*name))
+                            .cloned(),
+                    )
+                }
+            }
+            None => diff.added_types.push(path.clone()),
+        }
+    }
+    diff
+}
+
+/// The differences between the types of two registries, keyed by the path of each type.
+///
+/// All lists are sorted by path.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryDiff {
+    /// The paths of the types only present in the new registry.
+    pub added_types: Vec<String>,
+    /// The paths of the types only present in the old registry.
+    pub removed_types: Vec<String>,
+    /// The types present in both registries which changed.
+    pub changed_types: Vec<TypeDiff>,
+}
+
+impl RegistryDiff {
+    /// Returns `true` if the registries contain the same types.
+    pub fn is_empty(&self) -> bool {
+        self.added_types.is_empty()
+            && self.removed_types.is_empty()
+            && self.changed_types.is_empty()
+    }
+
+    fn push_changes(&mut self, path: String, changes: Vec<Change>) {
+        if !changes.is_empty() {
+            self.changed_types.push(TypeDiff { path, changes })
+        }
+    }
+}
+
+impl Display for RegistryDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for path in &self.added_types {
+            writeln!(f, "+ {path}")?;
+        }
+        for path in &self.removed_types {
+            writeln!(f, "- {path}")?;
+        }
+        for ty in &self.changed_types {
+            write!(f, "{ty}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The changes to a type present in both registries.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeDiff {
+    /// The path of the type.
+    pub path: String,
+    /// The changes to the type.
+    pub changes: Vec<Change>,
+}
+
+impl Display for TypeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "~ {}", self.path)?;
+        for change in &self.changes {
+            writeln!(f, "    {change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A change to a type.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The type changed to a different kind of type, e.g. from a struct to an enum.
+    KindChanged {
+        /// The kind of the old type.
+        old: String,
+        /// The kind of the new type.
+        new: String,
+    },
+    /// The names of the type parameters, or the types they are instantiated with, changed.
+    TypeParamsChanged {
+        /// The old type parameters, e.g. `T = u8`, or `T` for a parameter without a type.
+        old: Vec<String>,
+        /// The new type parameters.
+        new: Vec<String>,
+    },
+    /// A variant or field was added.
+    Added(Item),
+    /// A variant or field was removed.
+    Removed(Item),
+    /// The index of a variant, which identifies it in the encoding, changed.
+    VariantIndexChanged {
+        /// The name of the variant.
+        variant: String,
+        /// The old index.
+        old: u8,
+        /// The new index.
+        new: u8,
+    },
+    /// A named field moved to another position, which changes the encoding, other than by
+    /// being shifted by added or removed fields.
+    FieldMoved {
+        /// The field which moved.
+        item: Item,
+        /// The old position of the field.
+        old: usize,
+        /// The new position of the field.
+        new: usize,
+    },
+    /// The docs of the type, a variant or a field changed.
+    DocsChanged {
+        /// The item whose docs changed.
+        item: Item,
+        /// The old docs.
+        old: Vec<String>,
+        /// The new docs.
+        new: Vec<String>,
+    },
+    /// The type of a field changed, e.g. from `u64` to `u128`.
+    FieldTypeChanged {
+        /// The field whose type changed.
+        item: Item,
+        /// The old type, as rendered by [`PortableRegistry::display_type`].
+        old: String,
+        /// The new type.
+        new: String,
+    },
+    /// The type name of a field changed, while its type did not, e.g. when a type alias was
+    /// renamed.
+    FieldTypeNameChanged {
+        /// The field whose type name changed.
+        item: Item,
+        /// The old type name.
+        old: Option<String>,
+        /// The new type name.
+        new: Option<String>,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KindChanged { old, new } => write!(f, "~ kind: {old} -> {new}"),
+            Self::TypeParamsChanged { old, new } => {
+                write!(
+                    f,
+                    "~ type params: <{}> -> <{}>",
+                    old.join(", "),
+                    new.join(", ")
+                )
+            }
+            Self::Added(item) => write!(f, "+ {item}"),
+            Self::Removed(item) => write!(f, "- {item}"),
+            Self::VariantIndexChanged { variant, old, new } => {
+                write!(f, "~ index of variant `{variant}`: {old} -> {new}")
+            }
+            Self::FieldMoved { item, old, new } => {
+                write!(f, "~ position of {item}: {old} -> {new}")
+            }
+            Self::DocsChanged { item, .. } => write!(f, "~ docs of {item}"),
+            Self::FieldTypeChanged { item, old, new } => {
+                write!(f, "~ type of {item}: {old} -> {new}")
+            }
+            Self::FieldTypeNameChanged { item, old, new } => write!(
+                f,
+                "~ type name of {item}: {} -> {}",
+                old.as_deref().unwrap_or("_"),
+                new.as_deref().unwrap_or("_")
+            ),
+        }
+    }
+}
+
+/// A part of a type.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Item {
+    /// The type itself.
+    Type,
+    /// A variant of an enum.
+    Variant(String),
+    /// A field of a struct or of an enum variant.
+    Field {
+        /// The variant containing the field, if the type is an enum.
+        variant: Option<String>,
+        /// The name of the field, or its position for unnamed fields.
+        name: String,
+    },
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Type => write!(f, "type"),
+            Self::Variant(name) => write!(f, "variant `{name}`"),
+            Self::Field {
+                variant: Some(variant),
+                name,
+            } => write!(f, "field `{variant}.{name}`"),
+            Self::Field {
+                variant: None,
+                name,
+            } => write!(f, "field `{name}`"),
+        }
+    }
+}
+
+/// Returns the instantiations of each path, keyed by their rendering with their type
+/// parameters, e.g. `Option<u8>`, ignoring types without a path.
+fn types_by_path(
+    registry: &PortableRegistry,
+) -> BTreeMap<String, BTreeMap<String, &Type<PortableForm>>> {
+    let mut types = BTreeMap::new();
+    for (id, ty) in registry.types.iter().enumerate() {
+        if !ty.ty.path.is_empty() {
+            types
+                .entry(ty.ty.path.to_string())
+                .or_insert_with(BTreeMap::new)
+                .entry(registry.display_type(id as u32).to_string())
+                .or_insert(&ty.ty);
+        }
+    }
+    types
+}
+
+/// Returns the type of a path with a single instantiation.
+fn single<'a>(
+    instances: &BTreeMap<String, &'a Type<PortableForm>>,
+) -> Option<&'a Type<PortableForm>> {
+    match instances.len() {
+        1 => instances.values().next().copied(),
+        _ => None,
+    }
+}
+
+fn diff_types(
+    (old_registry, old): (&PortableRegistry, &Type<PortableForm>),
+    (new_registry, new): (&PortableRegistry, &Type<PortableForm>),
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let (old_params, new_params) = (
+        type_params(old_registry, old),
+        type_params(new_registry, new),
+    );
+    if old_params != new_params {
+        changes.push(Change::TypeParamsChanged {
+            old: old_params,
+            new: new_params,
+        })
+    }
+    diff_docs(&mut changes, Item::Type, &old.docs, &new.docs);
+
+    let registries = (old_registry, new_registry);
+    match (&old.type_def, &new.type_def) {
+        (TypeDef::Composite(old), TypeDef::Composite(new)) => {
+            diff_fields(&mut changes, registries, None, &old.fields, &new.fields)
+        }
+        (TypeDef::Variant(old), TypeDef::Variant(new)) => {
+            diff_variants(&mut changes, registries, &old.variants, &new.variants)
+        }
+        (old, new) => {
+            let (old, new) = (type_def_kind(old), type_def_kind(new));
+            if old != new {
+                changes.push(Change::KindChanged {
+                    old: old.to_string(),
+                    new: new.to_string(),
+                })
+            }
+        }
+    }
+    changes
+}
+
+/// Returns the type parameters of a type, along with the types they are instantiated with.
+fn type_params(registry: &PortableRegistry, ty: &Type<PortableForm>) -> Vec<String> {
+    ty.type_params
+        .iter()
+        .map(|param| match param.ty {
+            Some(ty) => format!("{} = {}", param.name, registry.display_type(ty.id)),
+            None => to_string(&param.name),
+        })
+        .collect()
+}
+
+fn diff_variants(
+    changes: &mut Vec<Change>,
+    registries: (&PortableRegistry, &PortableRegistry),
+    old: &[Variant<PortableForm>],
+    new: &[Variant<PortableForm>],
+) {
+    for old_variant in old {
+        let name = to_string(&old_variant.name);
+        match new.iter().find(|v| to_string(&v.name) == name) {
+            Some(new_variant) => {
+                if old_variant.index != new_variant.index {
+                    changes.push(Change::VariantIndexChanged {
+                        variant: name.clone(),
+                        old: old_variant.index,
+                        new: new_variant.index,
+                    })
+                }
+                let item = Item::Variant(name.clone());
+                diff_docs(changes, item, &old_variant.docs, &new_variant.docs);
+                diff_fields(
+                    changes,
+                    registries,
+                    Some(&name),
+                    &old_variant.fields,
+                    &new_variant.fields,
+                );
+            }
+            None => changes.push(Change::Removed(Item::Variant(name))),
+        }
+    }
+    for new_variant in new {
+        let name = to_string(&new_variant.name);
+        if !old.iter().any(|v| to_string(&v.name) == name) {
+            changes.push(Change::Added(Item::Variant(name)))
+        }
+    }
+}
+
+fn diff_fields(
+    changes: &mut Vec<Change>,
+    (old_registry, new_registry): (&PortableRegistry, &PortableRegistry),
+    variant: Option<&str>,
+    old: &[Field<PortableForm>],
+    new: &[Field<PortableForm>],
+) {
+    let item = |name: String| Item::Field {
+        variant: variant.map(ToString::to_string),
+        name,
+    };
+    let old_names: Vec<_> = old.iter().enumerate().map(field_name).collect();
+    let new_names: Vec<_> = new.iter().enumerate().map(field_name).collect();
+    // Fields are only reported as moved when their order relative to the fields present in
+    // both versions changed, not when they are shifted by added or removed fields.
+    let old_shared: Vec<_> = old_names.iter().filter(|n| new_names.contains(n)).collect();
+    let new_shared: Vec<_> = new_names.iter().filter(|n| old_names.contains(n)).collect();
+
+    for (old_position, (old_field, name)) in old.iter().zip(&old_names).enumerate() {
+        match new_names.iter().position(|n| n == name) {
+            Some(position) => {
+                let new_field = &new[position];
+                let (old_type, new_type) = (
+                    old_registry.display_type(old_field.ty.id).to_string(),
+                    new_registry.display_type(new_field.ty.id).to_string(),
+                );
+                let (old_type_name, new_type_name) = (
+                    old_field.type_name.as_ref().map(to_string),
+                    new_field.type_name.as_ref().map(to_string),
+                );
+                if old_type != new_type {
+                    changes.push(Change::FieldTypeChanged {
+                        item: item(name.clone()),
+                        old: old_type,
+                        new: new_type,
+                    })
+                } else if old_type_name != new_type_name {
+                    changes.push(Change::FieldTypeNameChanged {
+                        item: item(name.clone()),
+                        old: old_type_name,
+                        new: new_type_name,
+                    })
+                }
+                let rank = |shared: &[&String]| shared.iter().position(|n| *n == name);
+                if old_position != position && rank(&old_shared) != rank(&new_shared) {
+                    changes.push(Change::FieldMoved {
+                        item: item(name.clone()),
+                        old: old_position,
+                        new: position,
+                    })
+                }
+                diff_docs(
+                    changes,
+                    item(name.clone()),
+                    &old_field.docs,
+                    &new_field.docs,
+                );
+            }
+            None => changes.push(Change::Removed(item(name.clone()))),
+        }
+    }
+    for name in &new_names {
+        if !old_names.contains(name) {
+            changes.push(Change::Added(item(name.clone())))
+        }
+    }
+}
+
+fn diff_docs<S: AsRef<str>>(changes: &mut Vec<Change>, item: Item, old: &[S], new: &[S]) {
+    let (old, new): (Vec<_>, Vec<_>) = (
+        old.iter().map(to_string).collect(),
+        new.iter().map(to_string).collect(),
+    );
+    if old != new {
+        changes.push(Change::DocsChanged { item, old, new })
+    }
+}
+
+/// Returns the name of a field, or its position for unnamed fields.
+fn field_name((index, field): (usize, &Field<PortableForm>)) -> String {
+    match &field.name {
+        Some(name) => to_string(name),
+        None => index.to_string(),
+    }
+}
+
+fn to_string<S: AsRef<str>>(s: &S) -> String {
+    s.as_ref().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale_info_derive::TypeInfo;
+
+    fn registry_with<T: TypeInfo + 'static>() -> PortableRegistry {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<T>());
+        registry.into()
+    }
+
+    // Both versions live in their own module, which `rename` maps onto the same path.
+    #[allow(unused)]
+    mod v1 {
+        use super::*;
+
+        #[derive(TypeInfo)]
+        #[scale_info(skip_type_params(T))]
+        pub struct Wrapper<T> {
+            pub inner: core::marker::PhantomData<T>,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Pair<T> {
+            pub first: T,
+        }
+
+        pub type Balance = u64;
+
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub balance: Balance,
+            pub nonce: u32,
+        }
+
+        #[derive(TypeInfo)]
+        pub enum Event {
+            Created { id: u32, owner: u64 },
+            Updated { id: u32, owner: u64 },
+            Deleted,
+        }
+
+        pub mod types {
+            use super::*;
+
+            #[derive(TypeInfo)]
+            pub enum Call {
+                Transfer { to: u32, amount: u64 },
+                Remark(Vec<u8>),
+            }
+
+            #[derive(TypeInfo)]
+            pub struct Removed;
+
+            #[derive(TypeInfo)]
+            pub struct Root {
+                pub call: Call,
+                pub removed: Removed,
+                pub wrapper: Wrapper<u8>,
+            }
+        }
+    }
+
+    #[allow(unused)]
+    mod v2 {
+        use super::*;
+
+        #[derive(TypeInfo)]
+        #[scale_info(skip_type_params(T, U))]
+        pub struct Wrapper<T, U> {
+            pub inner: core::marker::PhantomData<(T, U)>,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Pair<T> {
+            pub first: T,
+            pub second: T,
+        }
+
+        pub type Balance = u128;
+        pub type Nonce = u32;
+
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub balance: Balance,
+            pub nonce: Nonce,
+        }
+
+        #[derive(TypeInfo)]
+        pub enum Event {
+            #[codec(index = 3)]
+            Created {
+                owner: u64,
+                id: u32,
+            },
+            Updated {
+                memo: Vec<u8>,
+                id: u32,
+                owner: u64,
+            },
+            Deleted,
+        }
+
+        pub mod types {
+            use super::*;
+
+            #[derive(TypeInfo)]
+            pub enum Call {
+                Transfer {
+                    to: u32,
+                    amount: u128,
+                    memo: Vec<u8>,
+                },
+                Burn,
+            }
+
+            #[derive(TypeInfo)]
+            pub struct Added;
+
+            #[derive(TypeInfo)]
+            pub struct Root {
+                pub call: Call,
+                pub added: Added,
+                pub wrapper: Wrapper<u8, u8>,
+            }
+        }
+    }
+
+    /// Maps the paths of both versions onto the same namespace.
+    fn rename(mut registry: PortableRegistry) -> PortableRegistry {
+        for ty in &mut registry.types {
+            for segment in &mut ty.ty.path.segments {
+                if segment == "v1" || segment == "v2" {
+                    *segment = "v".into();
+                }
+            }
+        }
+        registry
+    }
+
+    #[test]
+    fn diff_reports_changes_by_path() {
+        let old = rename(registry_with::<v1::types::Root>());
+        let new = rename(registry_with::<v2::types::Root>());
+        let diff = diff_registries(&old, &new);
+
+        let prefix = module_path!().to_string() + "::v";
+        let path = |ident: &str| format!("{prefix}::{ident}");
+        let field = |variant: &str, name: &str| Item::Field {
+            variant: Some(variant.into()),
+            name: name.into(),
+        };
+
+        assert_eq!(diff.added_types, vec![path("types::Added")]);
+        assert_eq!(diff.removed_types, vec![path("types::Removed")]);
+        assert_eq!(
+            diff.changed_types,
+            vec![
+                TypeDiff {
+                    path: path("Wrapper"),
+                    changes: vec![Change::TypeParamsChanged {
+                        old: vec!["T".into()],
+                        new: vec!["T".into(), "U".into()],
+                    },],
+                },
+                TypeDiff {
+                    path: path("types::Call"),
+                    changes: vec![
+                        Change::FieldTypeChanged {
+                            item: field("Transfer", "amount"),
+                            old: "u64".into(),
+                            new: "u128".into(),
+                        },
+                        Change::Added(field("Transfer", "memo")),
+                        Change::Removed(Item::Variant("Remark".into())),
+                        Change::Added(Item::Variant("Burn".into())),
+                    ],
+                },
+                TypeDiff {
+                    path: path("types::Root"),
+                    changes: vec![
+                        Change::Removed(Item::Field {
+                            variant: None,
+                            name: "removed".into()
+                        }),
+                        Change::FieldTypeChanged {
+                            item: Item::Field {
+                                variant: None,
+                                name: "wrapper".into()
+                            },
+                            old: path("Wrapper<T>"),
+                            new: path("Wrapper<T, U>"),
+                        },
+                        Change::Added(Item::Field {
+                            variant: None,
+                            name: "added".into()
+                        }),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string()
+                .lines()
+                .filter(|line| line.contains("Call") || line.starts_with("    "))
+                .take(5)
+                .collect::<Vec<_>>(),
+            vec![
+                "    ~ type params: <T> -> <T, U>",
+                &format!("~ {}", path("types::Call")),
+                "    ~ type of field `Transfer.amount`: u64 -> u128",
+                "    + field `Transfer.memo`",
+                "    - variant `Remark`",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_compares_every_instantiation() {
+        let old = rename(registry_with::<(v1::Pair<u8>, v1::Pair<bool>)>());
+        let new = rename(registry_with::<(v2::Pair<u8>, v2::Pair<u32>)>());
+        let diff = diff_registries(&old, &new);
+
+        let path = |params: &str| format!("{}::v::Pair<{params}>", module_path!());
+        assert_eq!(diff.added_types, vec![path("u32")]);
+        assert_eq!(diff.removed_types, vec![path("bool")]);
+        assert_eq!(
+            diff.changed_types,
+            vec![TypeDiff {
+                path: path("u8"),
+                changes: vec![Change::Added(Item::Field {
+                    variant: None,
+                    name: "second".into()
+                })],
+            }]
+        );
+
+        // A single instantiation on each side is compared as is.
+        let old = rename(registry_with::<v1::Pair<u8>>());
+        let new = rename(registry_with::<v1::Pair<u32>>());
+        let diff = diff_registries(&old, &new);
+        assert!(diff.added_types.is_empty() && diff.removed_types.is_empty());
+        assert_eq!(
+            diff.changed_types,
+            vec![TypeDiff {
+                path: format!("{}::v::Pair", module_path!()),
+                changes: vec![
+                    Change::TypeParamsChanged {
+                        old: vec!["T = u8".into()],
+                        new: vec!["T = u32".into()],
+                    },
+                    Change::FieldTypeChanged {
+                        item: Item::Field {
+                            variant: None,
+                            name: "first".into()
+                        },
+                        old: "u8".into(),
+                        new: "u32".into(),
+                    },
+                ],
+            }]
+        );
+        assert!(diff
+            .to_string()
+            .contains("~ type params: <T = u8> -> <T = u32>"));
+    }
+
+    #[test]
+    fn diff_reports_doc_changes() {
+        let mut old = registry_with::<Option<u8>>();
+        let new = old.clone();
+        assert!(diff_registries(&old, &new).is_empty());
+        assert_eq!(diff_registries(&old, &new).to_string(), "no changes\n");
+
+        old.types[0].ty.docs = vec!["An optional value.".into()];
+        let diff = diff_registries(&old, &new);
+        assert_eq!(
+            diff.changed_types[0].changes,
+            vec![Change::DocsChanged {
+                item: Item::Type,
+                old: vec!["An optional value.".into()],
+                new: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_compares_field_types() {
+        let old = rename(registry_with::<v1::Account>());
+        let new = rename(registry_with::<v2::Account>());
+        let diff = diff_registries(&old, &new);
+
+        let field = |name: &str| Item::Field {
+            variant: None,
+            name: name.into(),
+        };
+        // The type name of `balance` is the same, but the alias now refers to another type.
+        assert_eq!(
+            diff.changed_types[0].changes,
+            vec![
+                Change::FieldTypeChanged {
+                    item: field("balance"),
+                    old: "u64".into(),
+                    new: "u128".into(),
+                },
+                Change::FieldTypeNameChanged {
+                    item: field("nonce"),
+                    old: Some("u32".into()),
+                    new: Some("Nonce".into()),
+                },
+            ]
+        );
+        assert!(diff
+            .to_string()
+            .contains("~ type name of field `nonce`: u32 -> Nonce"));
+    }
+
+    #[test]
+    fn diff_reports_encoding_changes() {
+        let old = rename(registry_with::<v1::Event>());
+        let new = rename(registry_with::<v2::Event>());
+        let diff = diff_registries(&old, &new);
+
+        let field = |variant: &str, name: &str| Item::Field {
+            variant: Some(variant.into()),
+            name: name.into(),
+        };
+        // The fields of `Updated` are only shifted by the added field.
+        assert_eq!(
+            diff.changed_types[0].changes,
+            vec![
+                Change::VariantIndexChanged {
+                    variant: "Created".into(),
+                    old: 0,
+                    new: 3,
+                },
+                Change::FieldMoved {
+                    item: field("Created", "id"),
+                    old: 0,
+                    new: 1,
+                },
+                Change::FieldMoved {
+                    item: field("Created", "owner"),
+                    old: 1,
+                    new: 0,
+                },
+                Change::Added(field("Updated", "memo")),
+            ]
+        );
+        assert!(diff.to_string().contains(
+            "    ~ index of variant `Created`: 0 -> 3\n    ~ position of field `Created.id`: 0 -> 1\n"
+        ));
+    }
+}
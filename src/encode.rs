@@ -0,0 +1,976 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of dynamic [`Value`]s into SCALE bytes.
+//!
+//! The value is validated against the [`TypeDef`] of the target type while it is being encoded,
+//! and any mismatch is reported together with the path of the offending field.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{encode::encode_value, value::{Composite, Value}, MetaType, PortableRegistry, Registry};
+//! # use scale::Encode;
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<Option<(u8, bool)>>()).id;
+//! let registry: PortableRegistry = registry.into();
+//!
+//! let value = Value::variant(
+//!     "Some",
+//!     Composite::Unnamed(vec![Value::Tuple(vec![Value::from(7u8), Value::bool(true)])]),
+//! );
+//! let mut encoded = Vec::new();
+//! encode_value(&registry, id, &value, &mut encoded).unwrap();
+//!
+//! assert_eq!(encoded, Some((7u8, true)).encode());
+//! ```
+
+use crate::prelude::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    form::PortableForm,
+    utils::{bit_order_is_lsb0, bit_store_bytes, primitive_kind},
+    value::{Composite, Primitive, Value},
+    Field, PortableRegistry, TypeDef, TypeDefBitSequence, TypeDefPrimitive,
+};
+use scale::{Compact, Encode};
+
+/// The nesting depth up to which [`encode_value`] encodes values.
+pub const DEFAULT_DEPTH_LIMIT: u32 = 256;
+
+/// An error that may be encountered when encoding a [`Value`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodeError {
+    /// The location of the offending value, starting from the root value.
+    pub path: Vec<PathSegment>,
+    /// The reason the value could not be encoded.
+    pub kind: EncodeErrorKind,
+}
+
+impl EncodeError {
+    fn new(kind: EncodeErrorKind) -> Self {
+        Self {
+            path: Vec::new(),
+            kind,
+        }
+    }
+
+    /// Prepends the given segment to the path of the error.
+    fn at(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return write!(f, "{}", self.kind);
+        }
+        f.write_str("at ")?;
+        for segment in &self.path {
+            write!(f, "{segment}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+/// A segment of the path to a value nested within another value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A named field.
+    Field(String),
+    /// The position of an unnamed field, tuple element or sequence item.
+    Index(usize),
+    /// An enum variant.
+    Variant(String),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{name}"),
+            Self::Index(index) => write!(f, "[{index}]"),
+            Self::Variant(name) => write!(f, "::{name}"),
+        }
+    }
+}
+
+/// The reason a [`Value`] could not be encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodeErrorKind {
+    /// The type id could not be found in the registry.
+    TypeNotFound(u32),
+    /// The shape of the value does not match the shape of the type.
+    TypeMismatch {
+        /// The id of the type the value was encoded as.
+        type_id: u32,
+        /// The kind of value expected by the type.
+        expected: &'static str,
+        /// The kind of value found.
+        found: &'static str,
+    },
+    /// The number of fields or items does not match the type.
+    WrongLength {
+        /// The number of fields or items expected by the type.
+        expected: usize,
+        /// The number of fields or items found.
+        found: usize,
+    },
+    /// A field required by the type is missing from the value.
+    MissingField(String),
+    /// The value has a field which is not defined by the type.
+    UnexpectedField(String),
+    /// The value has more than one field with the given name.
+    DuplicateField(String),
+    /// The variant type has no variant with the given name.
+    UnknownVariant(String),
+    /// An integer does not fit into the primitive type it is encoded as.
+    IntegerOutOfRange {
+        /// The id of the primitive type.
+        type_id: u32,
+    },
+    /// The type with the given id can not be compact encoded.
+    UnsupportedCompactType(u32),
+    /// The bit store type with the given id is not one of `u8`, `u16`, `u32` or `u64`.
+    UnsupportedBitStoreType(u32),
+    /// The bit order type with the given id is neither `Lsb0` nor `Msb0`.
+    UnsupportedBitOrderType(u32),
+    /// The value is nested deeper than the given limit.
+    DepthLimitExceeded(u32),
+    /// The length of a sequence or bit sequence does not fit into the `u32` it is encoded as.
+    LengthOutOfRange(usize),
+}
+
+impl Display for EncodeErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeNotFound(id) => write!(f, "type with id {id} not found in the registry"),
+            Self::TypeMismatch {
+                type_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "type with id {type_id} expects a {expected} value but found a {found} value"
+            ),
+            Self::WrongLength { expected, found } => {
+                write!(f, "expected {expected} fields or items but found {found}")
+            }
+            Self::MissingField(name) => write!(f, "missing field `{name}`"),
+            Self::UnexpectedField(name) => write!(f, "unexpected field `{name}`"),
+            Self::DuplicateField(name) => write!(f, "duplicate field `{name}`"),
+            Self::UnknownVariant(name) => write!(f, "unknown variant `{name}`"),
+            Self::IntegerOutOfRange { type_id } => {
+                write!(f, "integer out of range for type with id {type_id}")
+            }
+            Self::UnsupportedCompactType(id) => {
+                write!(f, "type with id {id} can not be compact encoded")
+            }
+            Self::UnsupportedBitStoreType(id) => {
+                write!(f, "type with id {id} is not a supported bit store type")
+            }
+            Self::UnsupportedBitOrderType(id) => {
+                write!(f, "type with id {id} is not a supported bit order type")
+            }
+            Self::DepthLimitExceeded(limit) => {
+                write!(f, "value nested deeper than the limit of {limit}")
+            }
+            Self::LengthOutOfRange(len) => {
+                write!(f, "length {len} does not fit into a compact u32")
+            }
+        }
+    }
+}
+
+/// Encodes the given [`Value`] as the type with the given id, appending the bytes to `output`.
+///
+/// Values are matched against the type leniently where this is unambiguous:
+///
+/// - Named fields may be given in any order, and also positionally as unnamed fields.
+/// - Variants are looked up by name; the [`index`](crate::value::VariantValue::index) of the
+///   value is ignored.
+/// - A composite with a single field may be given as the value of that field.
+/// - Any integer which fits into the target integer type is accepted.
+/// - A value encoded as a [`TypeDef::Compact`] need not be wrapped in [`Value::Compact`].
+/// - [`Value::Sequence`] and [`Value::Array`] are interchangeable.
+///
+/// Values nested deeper than [`DEFAULT_DEPTH_LIMIT`] are rejected, so that a type referring to
+/// itself can't overflow the stack. On error, `output` may contain a partially encoded value.
+pub fn encode_value(
+    registry: &PortableRegistry,
+    type_id: u32,
+    value: &Value,
+    output: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    encode_value_with_depth_limit(registry, type_id, value, output, DEFAULT_DEPTH_LIMIT)
+}
+
+/// Encodes a [`Value`] like [`encode_value`], rejecting values nested deeper than the given
+/// limit with [`EncodeErrorKind::DepthLimitExceeded`].
+///
+/// Every composite, variant, sequence, array, tuple and compact counts as one level, including
+/// the composites with a single field given as the value of that field.
+pub fn encode_value_with_depth_limit(
+    registry: &PortableRegistry,
+    type_id: u32,
+    value: &Value,
+    output: &mut Vec<u8>,
+    depth_limit: u32,
+) -> Result<(), EncodeError> {
+    Encoder {
+        registry,
+        depth_limit,
+    }
+    .encode_value(type_id, value, output, 0)
+}
+
+/// The registry and limit of a value being encoded.
+struct Encoder<'a> {
+    registry: &'a PortableRegistry,
+    depth_limit: u32,
+}
+
+impl Encoder<'_> {
+    fn encode_value(
+        &self,
+        type_id: u32,
+        value: &Value,
+        output: &mut Vec<u8>,
+        depth: u32,
+    ) -> Result<(), EncodeError> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit && !matches!(ty.type_def, TypeDef::Primitive(_)) {
+            return Err(EncodeError::new(EncodeErrorKind::DepthLimitExceeded(
+                self.depth_limit,
+            )));
+        }
+        let depth = depth + 1;
+        let mismatch = |expected| {
+            EncodeError::new(EncodeErrorKind::TypeMismatch {
+                type_id,
+                expected,
+                found: value_kind(value),
+            })
+        };
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) => match value {
+                Value::Composite(fields) => {
+                    self.encode_fields(&composite.fields, fields, output, depth)
+                }
+                _ if composite.fields.len() == 1 => {
+                    let field = &composite.fields[0];
+                    self.encode_value(field.ty.id, value, output, depth)
+                        .map_err(|e| e.at(field_segment(field, 0)))
+                }
+                _ => Err(mismatch("composite")),
+            },
+            TypeDef::Variant(variant) => {
+                let Value::Variant(value) = value else {
+                    return Err(mismatch("variant"));
+                };
+                let variant = variant
+                    .variants
+                    .iter()
+                    .find(|v| v.name.as_ref() == value.name)
+                    .ok_or_else(|| {
+                        EncodeError::new(EncodeErrorKind::UnknownVariant(value.name.clone()))
+                    })?;
+                output.push(variant.index);
+                self.encode_fields(&variant.fields, &value.fields, output, depth)
+                    .map_err(|e| e.at(PathSegment::Variant(value.name.clone())))
+            }
+            TypeDef::Sequence(sequence) => {
+                let (Value::Sequence(items) | Value::Array(items)) = value else {
+                    return Err(mismatch("sequence"));
+                };
+                encode_len(items.len(), output)?;
+                self.encode_items(sequence.type_param.id, items, output, depth)
+            }
+            TypeDef::Array(array) => {
+                let (Value::Sequence(items) | Value::Array(items)) = value else {
+                    return Err(mismatch("array"));
+                };
+                check_len(array.len as usize, items.len())?;
+                self.encode_items(array.type_param.id, items, output, depth)
+            }
+            TypeDef::Tuple(tuple) => {
+                let items = match value {
+                    Value::Tuple(items) | Value::Composite(Composite::Unnamed(items)) => items,
+                    _ => return Err(mismatch("tuple")),
+                };
+                check_len(tuple.fields.len(), items.len())?;
+                for (i, (ty, item)) in tuple.fields.iter().zip(items).enumerate() {
+                    self.encode_value(ty.id, item, output, depth)
+                        .map_err(|e| e.at(PathSegment::Index(i)))?;
+                }
+                Ok(())
+            }
+            TypeDef::Primitive(primitive) => {
+                let Value::Primitive(value) = value else {
+                    return Err(mismatch(primitive_kind(primitive)));
+                };
+                encode_primitive(type_id, primitive, value, output)
+            }
+            TypeDef::Compact(compact) => {
+                let value = match value {
+                    Value::Compact(value) => value,
+                    value => value,
+                };
+                self.encode_compact(compact.type_param.id, value, output, depth)
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                let Value::BitSequence(bits) = value else {
+                    return Err(mismatch("bit sequence"));
+                };
+                encode_bit_sequence(self.registry, bit_sequence, bits, output)
+            }
+        }
+    }
+
+    fn encode_fields(
+        &self,
+        fields: &[Field<PortableForm>],
+        values: &Composite,
+        output: &mut Vec<u8>,
+        depth: u32,
+    ) -> Result<(), EncodeError> {
+        let named_type = !fields.is_empty() && fields.iter().all(|f| f.name.is_some());
+        match values {
+            Composite::Named(values) if named_type => {
+                if let Some((name, _)) = values
+                    .iter()
+                    .find(|(name, _)| !fields.iter().any(|f| field_name(f) == Some(name)))
+                {
+                    return Err(EncodeError::new(EncodeErrorKind::UnexpectedField(
+                        name.clone(),
+                    )));
+                }
+                if let Some((name, _)) = values
+                    .iter()
+                    .enumerate()
+                    .find(|(i, (name, _))| values[..*i].iter().any(|(n, _)| n == name))
+                    .map(|(_, field)| field)
+                {
+                    return Err(EncodeError::new(EncodeErrorKind::DuplicateField(
+                        name.clone(),
+                    )));
+                }
+                for (i, field) in fields.iter().enumerate() {
+                    let name = field_name(field).unwrap_or_default();
+                    let (_, value) = values.iter().find(|(n, _)| n == name).ok_or_else(|| {
+                        EncodeError::new(EncodeErrorKind::MissingField(name.to_string()))
+                    })?;
+                    self.encode_value(field.ty.id, value, output, depth)
+                        .map_err(|e| e.at(field_segment(field, i)))?;
+                }
+                Ok(())
+            }
+            Composite::Named(values) if fields.is_empty() => match values.first() {
+                Some((name, _)) => Err(EncodeError::new(EncodeErrorKind::UnexpectedField(
+                    name.clone(),
+                ))),
+                None => Ok(()),
+            },
+            Composite::Named(values) => {
+                check_len(fields.len(), values.len())?;
+                Err(EncodeError::new(EncodeErrorKind::UnexpectedField(
+                    values[0].0.clone(),
+                )))
+            }
+            Composite::Unnamed(values) => {
+                check_len(fields.len(), values.len())?;
+                for (i, (field, value)) in fields.iter().zip(values).enumerate() {
+                    self.encode_value(field.ty.id, value, output, depth)
+                        .map_err(|e| e.at(field_segment(field, i)))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn encode_items(
+        &self,
+        type_id: u32,
+        items: &[Value],
+        output: &mut Vec<u8>,
+        depth: u32,
+    ) -> Result<(), EncodeError> {
+        for (i, item) in items.iter().enumerate() {
+            self.encode_value(type_id, item, output, depth)
+                .map_err(|e| e.at(PathSegment::Index(i)))?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a value wrapped in a `Compact`, which is either an unsigned integer or a composite
+    /// or tuple with a single field wrapping one.
+    fn encode_compact(
+        &self,
+        type_id: u32,
+        value: &Value,
+        output: &mut Vec<u8>,
+        depth: u32,
+    ) -> Result<(), EncodeError> {
+        let ty = resolve(self.registry, type_id)?;
+        if depth >= self.depth_limit {
+            return Err(EncodeError::new(EncodeErrorKind::DepthLimitExceeded(
+                self.depth_limit,
+            )));
+        }
+        let depth = depth + 1;
+        let mismatch = |expected| {
+            EncodeError::new(EncodeErrorKind::TypeMismatch {
+                type_id,
+                expected,
+                found: value_kind(value),
+            })
+        };
+
+        match &ty.type_def {
+            TypeDef::Primitive(primitive) => {
+                let max = match primitive {
+                    TypeDefPrimitive::U8 => u128::from(u8::MAX),
+                    TypeDefPrimitive::U16 => u128::from(u16::MAX),
+                    TypeDefPrimitive::U32 => u128::from(u32::MAX),
+                    TypeDefPrimitive::U64 => u128::from(u64::MAX),
+                    TypeDefPrimitive::U128 => u128::MAX,
+                    _ => {
+                        return Err(EncodeError::new(EncodeErrorKind::UnsupportedCompactType(
+                            type_id,
+                        )))
+                    }
+                };
+                let n = match value {
+                    Value::Primitive(p) => as_u128(p).ok_or_else(|| {
+                        if as_i128(p).is_some() {
+                            EncodeError::new(EncodeErrorKind::IntegerOutOfRange { type_id })
+                        } else {
+                            mismatch(primitive_kind(primitive))
+                        }
+                    })?,
+                    _ => return Err(mismatch(primitive_kind(primitive))),
+                };
+                if n > max {
+                    return Err(EncodeError::new(EncodeErrorKind::IntegerOutOfRange {
+                        type_id,
+                    }));
+                }
+                Compact(n).encode_to(output);
+                Ok(())
+            }
+            TypeDef::Composite(composite) => match &composite.fields[..] {
+                [] => match value {
+                    Value::Composite(fields) if fields.is_empty() => Ok(()),
+                    _ => Err(mismatch("composite")),
+                },
+                [field] => {
+                    let value = match value {
+                        Value::Composite(Composite::Named(fields)) if fields.len() == 1 => {
+                            let (name, value) = &fields[0];
+                            if field_name(field) != Some(name.as_str()) {
+                                return Err(EncodeError::new(EncodeErrorKind::UnexpectedField(
+                                    name.clone(),
+                                )));
+                            }
+                            value
+                        }
+                        Value::Composite(Composite::Unnamed(fields)) if fields.len() == 1 => {
+                            &fields[0]
+                        }
+                        Value::Composite(fields) => {
+                            return Err(EncodeError::new(EncodeErrorKind::WrongLength {
+                                expected: 1,
+                                found: fields.len(),
+                            }))
+                        }
+                        value => value,
+                    };
+                    self.encode_compact(field.ty.id, value, output, depth)
+                        .map_err(|e| e.at(field_segment(field, 0)))
+                }
+                _ => Err(EncodeError::new(EncodeErrorKind::UnsupportedCompactType(
+                    type_id,
+                ))),
+            },
+            TypeDef::Tuple(tuple) => match &tuple.fields[..] {
+                [] => match value {
+                    Value::Tuple(items) if items.is_empty() => Ok(()),
+                    _ => Err(mismatch("tuple")),
+                },
+                [field] => {
+                    let value = match value {
+                        Value::Tuple(items) if items.len() == 1 => &items[0],
+                        value => value,
+                    };
+                    self.encode_compact(field.id, value, output, depth)
+                        .map_err(|e| e.at(PathSegment::Index(0)))
+                }
+                _ => Err(EncodeError::new(EncodeErrorKind::UnsupportedCompactType(
+                    type_id,
+                ))),
+            },
+            _ => Err(EncodeError::new(EncodeErrorKind::UnsupportedCompactType(
+                type_id,
+            ))),
+        }
+    }
+}
+
+fn resolve(
+    registry: &PortableRegistry,
+    type_id: u32,
+) -> Result<&crate::Type<PortableForm>, EncodeError> {
+    registry
+        .resolve(type_id)
+        .ok_or_else(|| EncodeError::new(EncodeErrorKind::TypeNotFound(type_id)))
+}
+
+fn check_len(expected: usize, found: usize) -> Result<(), EncodeError> {
+    if expected != found {
+        return Err(EncodeError::new(EncodeErrorKind::WrongLength {
+            expected,
+            found,
+        }));
+    }
+    Ok(())
+}
+
+fn encode_len(len: usize, output: &mut Vec<u8>) -> Result<(), EncodeError> {
+    let len =
+        u32::try_from(len).map_err(|_| EncodeError::new(EncodeErrorKind::LengthOutOfRange(len)))?;
+    Compact(len).encode_to(output);
+    Ok(())
+}
+
+fn field_segment(field: &Field<PortableForm>, index: usize) -> PathSegment {
+    match field_name(field) {
+        Some(name) => PathSegment::Field(name.to_string()),
+        None => PathSegment::Index(index),
+    }
+}
+
+fn field_name(field: &Field<PortableForm>) -> Option<&str> {
+    field.name.as_ref().map(AsRef::as_ref)
+}
+
+fn encode_primitive(
+    type_id: u32,
+    primitive: &TypeDefPrimitive,
+    value: &Primitive,
+    output: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    let mismatch = || {
+        EncodeError::new(EncodeErrorKind::TypeMismatch {
+            type_id,
+            expected: primitive_kind(primitive),
+            found: primitive_value_kind(value),
+        })
+    };
+    let out_of_range = || EncodeError::new(EncodeErrorKind::IntegerOutOfRange { type_id });
+
+    match (primitive, value) {
+        (TypeDefPrimitive::Bool, Primitive::Bool(b)) => b.encode_to(output),
+        (TypeDefPrimitive::Char, Primitive::Char(c)) => (*c as u32).encode_to(output),
+        (TypeDefPrimitive::Str, Primitive::Str(s)) => s.encode_to(output),
+        (TypeDefPrimitive::U256, Primitive::U256(bytes))
+        | (TypeDefPrimitive::I256, Primitive::I256(bytes)) => output.extend_from_slice(bytes),
+        (TypeDefPrimitive::U256, value) => {
+            let n = as_u128(value).ok_or_else(mismatch)?;
+            let mut bytes = [0u8; 32];
+            bytes[..16].copy_from_slice(&n.to_le_bytes());
+            output.extend_from_slice(&bytes);
+        }
+        (TypeDefPrimitive::I256, value) => {
+            let n = as_i128(value).ok_or_else(mismatch)?;
+            let fill = if n < 0 { 0xff } else { 0 };
+            let mut bytes = [fill; 32];
+            bytes[..16].copy_from_slice(&n.to_le_bytes());
+            output.extend_from_slice(&bytes);
+        }
+        (primitive, value) => {
+            let n = as_i128(value)
+                .map(Ok)
+                .or_else(|| as_u128(value).map(Err))
+                .ok_or_else(mismatch)?;
+            macro_rules! encode_int {
+                ( $( $variant:ident => $t:ty, )* ) => {
+                    match primitive {
+                        $(
+                            TypeDefPrimitive::$variant => {
+                                let n = match n {
+                                    Ok(n) => <$t>::try_from(n).ok(),
+                                    Err(n) => <$t>::try_from(n).ok(),
+                                };
+                                n.ok_or_else(out_of_range)?.encode_to(output)
+                            }
+                        )*
+                        _ => return Err(mismatch()),
+                    }
+                }
+            }
+            encode_int!(
+                U8 => u8,
+                U16 => u16,
+                U32 => u32,
+                U64 => u64,
+                U128 => u128,
+                I8 => i8,
+                I16 => i16,
+                I32 => i32,
+                I64 => i64,
+                I128 => i128,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn encode_bit_sequence(
+    registry: &PortableRegistry,
+    bit_sequence: &TypeDefBitSequence<PortableForm>,
+    bits: &[bool],
+    output: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    let store_id = bit_sequence.bit_store_type.id;
+    let store_bytes = bit_store_bytes(resolve(registry, store_id)?)
+        .ok_or_else(|| EncodeError::new(EncodeErrorKind::UnsupportedBitStoreType(store_id)))?;
+    let order_id = bit_sequence.bit_order_type.id;
+    let lsb0 = bit_order_is_lsb0(resolve(registry, order_id)?)
+        .ok_or_else(|| EncodeError::new(EncodeErrorKind::UnsupportedBitOrderType(order_id)))?;
+    let store_bits = store_bytes * 8;
+
+    encode_len(bits.len(), output)?;
+    for chunk in bits.chunks(store_bits) {
+        let mut element = 0u64;
+        for (i, bit) in chunk.iter().enumerate() {
+            let shift = if lsb0 { i } else { store_bits - 1 - i };
+            element |= u64::from(*bit) << shift;
+        }
+        output.extend_from_slice(&element.to_le_bytes()[..store_bytes]);
+    }
+    Ok(())
+}
+
+fn as_u128(value: &Primitive) -> Option<u128> {
+    match *value {
+        Primitive::U8(n) => Some(n.into()),
+        Primitive::U16(n) => Some(n.into()),
+        Primitive::U32(n) => Some(n.into()),
+        Primitive::U64(n) => Some(n.into()),
+        Primitive::U128(n) => Some(n),
+        Primitive::I8(n) => u128::try_from(n).ok(),
+        Primitive::I16(n) => u128::try_from(n).ok(),
+        Primitive::I32(n) => u128::try_from(n).ok(),
+        Primitive::I64(n) => u128::try_from(n).ok(),
+        Primitive::I128(n) => u128::try_from(n).ok(),
+        _ => None,
+    }
+}
+
+fn as_i128(value: &Primitive) -> Option<i128> {
+    match *value {
+        Primitive::U8(n) => Some(n.into()),
+        Primitive::U16(n) => Some(n.into()),
+        Primitive::U32(n) => Some(n.into()),
+        Primitive::U64(n) => Some(n.into()),
+        Primitive::U128(n) => i128::try_from(n).ok(),
+        Primitive::I8(n) => Some(n.into()),
+        Primitive::I16(n) => Some(n.into()),
+        Primitive::I32(n) => Some(n.into()),
+        Primitive::I64(n) => Some(n.into()),
+        Primitive::I128(n) => Some(n),
+        _ => None,
+    }
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Composite(_) => "composite",
+        Value::Variant(_) => "variant",
+        Value::Sequence(_) => "sequence",
+        Value::Array(_) => "array",
+        Value::Tuple(_) => "tuple",
+        Value::Primitive(primitive) => primitive_value_kind(primitive),
+        Value::Compact(_) => "compact",
+        Value::BitSequence(_) => "bit sequence",
+    }
+}
+
+fn primitive_value_kind(value: &Primitive) -> &'static str {
+    match value {
+        Primitive::Bool(_) => "bool",
+        Primitive::Char(_) => "char",
+        Primitive::Str(_) => "str",
+        Primitive::U8(_) => "u8",
+        Primitive::U16(_) => "u16",
+        Primitive::U32(_) => "u32",
+        Primitive::U64(_) => "u64",
+        Primitive::U128(_) => "u128",
+        Primitive::U256(_) => "u256",
+        Primitive::I8(_) => "i8",
+        Primitive::I16(_) => "i16",
+        Primitive::I32(_) => "i32",
+        Primitive::I64(_) => "i64",
+        Primitive::I128(_) => "i128",
+        Primitive::I256(_) => "i256",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode::decode_value, prelude::vec, *};
+    use scale::Encode;
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Encode)]
+    struct Transfer {
+        dest: [u8; 4],
+        #[codec(compact)]
+        amount: u128,
+        memo: Option<String>,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Encode)]
+    enum Call {
+        Remark(Vec<u8>),
+        #[codec(index = 7)]
+        Transfer(Transfer),
+    }
+
+    fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        (id, registry.into())
+    }
+
+    fn encode(registry: &PortableRegistry, id: u32, value: &Value) -> Result<Vec<u8>, EncodeError> {
+        let mut output = Vec::new();
+        encode_value(registry, id, value, &mut output).map(|()| output)
+    }
+
+    fn transfer(amount: Value) -> Value {
+        Value::variant(
+            "Transfer",
+            Composite::Unnamed(vec![Value::named_composite([
+                ("memo", Value::variant("None", Composite::unit())),
+                ("amount", amount),
+                (
+                    "dest",
+                    Value::Array(vec![1u8.into(), 2u8.into(), 3u8.into(), 4u8.into()]),
+                ),
+            ])]),
+        )
+    }
+
+    #[test]
+    fn encode_matches_codec() {
+        let (id, registry) = registry_with::<Call>();
+        let expected = Call::Transfer(Transfer {
+            dest: [1, 2, 3, 4],
+            amount: 1_000,
+            memo: None,
+        })
+        .encode();
+
+        // Integers are accepted for compact fields, with or without the `Compact` wrapper.
+        let value = transfer(Value::from(1_000u32));
+        assert_eq!(encode(&registry, id, &value), Ok(expected.clone()));
+        let value = transfer(Value::compact(Value::from(1_000u128)));
+        assert_eq!(encode(&registry, id, &value), Ok(expected));
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        let (id, registry) = registry_with::<(Call, Vec<(bool, i32)>, char)>();
+        let encoded = (
+            Call::Remark(vec![1, 2]),
+            vec![(true, -1), (false, i32::MAX)],
+            // `char` isn't `Encode`, so encode it as its `u32` representation.
+            'x' as u32,
+        )
+            .encode();
+
+        let value = decode_value(&registry, id, &mut &encoded[..]).unwrap();
+        assert_eq!(encode(&registry, id, &value), Ok(encoded));
+    }
+
+    #[test]
+    fn errors_point_at_offending_field() {
+        let (id, registry) = registry_with::<Call>();
+
+        let err = encode(&registry, id, &transfer(Value::from(-1i8))).unwrap_err();
+        assert_eq!(
+            err.path,
+            vec![
+                PathSegment::Variant("Transfer".into()),
+                PathSegment::Index(0),
+                PathSegment::Field("amount".into()),
+            ]
+        );
+        assert!(matches!(
+            err.kind,
+            EncodeErrorKind::IntegerOutOfRange { .. }
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "at ::Transfer[0].amount: integer out of range for type with id {}",
+                registry
+                    .types
+                    .iter()
+                    .find(|t| matches!(t.ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U128)))
+                    .unwrap()
+                    .id
+            )
+        );
+
+        let value = Value::variant(
+            "Remark",
+            Composite::Unnamed(vec![Value::Sequence(vec![1u8.into(), Value::bool(true)])]),
+        );
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(
+            err.path,
+            vec![
+                PathSegment::Variant("Remark".into()),
+                PathSegment::Index(0),
+                PathSegment::Index(1),
+            ]
+        );
+
+        let value = Value::variant("Unknown", Composite::unit());
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(err.kind, EncodeErrorKind::UnknownVariant("Unknown".into()));
+    }
+
+    #[test]
+    fn encode_bit_sequence() {
+        let mut builder = PortableRegistryBuilder::new();
+        let u8_id = builder.register_type(TypeDefPrimitive::U8.into());
+        let msb0_id = builder.register_type(
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked(["Msb0".into()]))
+                .composite(build::Fields::unit()),
+        );
+        let id = builder
+            .register_type(TypeDefBitSequence::new_portable(u8_id.into(), msb0_id.into()).into());
+        let registry = builder.finish();
+
+        let bits = vec![
+            true, false, false, true, true, false, true, false, true, true,
+        ];
+        let encoded = encode(&registry, id, &Value::BitSequence(bits.clone())).unwrap();
+        assert_eq!(encoded, vec![10 << 2, 0b1001_1010, 0b1100_0000]);
+        assert_eq!(
+            decode_value(&registry, id, &mut &encoded[..]),
+            Ok(Value::BitSequence(bits))
+        );
+    }
+
+    #[test]
+    fn missing_unexpected_and_duplicate_fields() {
+        let (id, registry) = registry_with::<Transfer>();
+
+        let value = Value::named_composite([("dest", Value::Array(vec![0u8.into(); 4]))]);
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(err.kind, EncodeErrorKind::MissingField("amount".into()));
+
+        let value = Value::named_composite([("foo", Value::bool(true))]);
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(err.kind, EncodeErrorKind::UnexpectedField("foo".into()));
+
+        let value = Value::named_composite([
+            ("dest", Value::Array(vec![0u8.into(); 4])),
+            ("amount", Value::compact(Value::from(1u128))),
+            ("memo", Value::variant("None", Composite::unit())),
+            ("dest", Value::Array(vec![1u8.into(); 4])),
+        ]);
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(err.kind, EncodeErrorKind::DuplicateField("dest".into()));
+    }
+
+    #[test]
+    fn compact_wrapper_field_names() {
+        let mut builder = PortableRegistryBuilder::new();
+        let u64_id = builder.register_type(TypeDefPrimitive::U64.into());
+        let amount_id = builder.register_type(
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked(["Amount".into()]))
+                .composite(
+                    build::Fields::named().field_portable(|f| f.name("value".into()).ty(u64_id)),
+                ),
+        );
+        let id = builder.register_type(TypeDefCompact::new(amount_id.into()).into());
+        let registry = builder.finish();
+
+        let value = Value::named_composite([("value", Value::from(5u64))]);
+        assert_eq!(
+            encode(&registry, id, &value),
+            Ok(scale::Compact(5u64).encode())
+        );
+
+        let value = Value::named_composite([("other", Value::from(5u64))]);
+        let err = encode(&registry, id, &value).unwrap_err();
+        assert_eq!(err.kind, EncodeErrorKind::UnexpectedField("other".into()));
+    }
+
+    #[test]
+    fn encode_depth_limit() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Loop(Box<Loop>);
+
+        // Every level accepts the value as the value of its single field.
+        let (id, registry) = registry_with::<Loop>();
+        let err = encode(&registry, id, &Value::from(1u8)).unwrap_err();
+        assert_eq!(
+            err.kind,
+            EncodeErrorKind::DepthLimitExceeded(DEFAULT_DEPTH_LIMIT)
+        );
+
+        let (id, registry) = registry_with::<Vec<Vec<u8>>>();
+        let value = Value::Sequence(vec![Value::Sequence(vec![1u8.into()])]);
+        let mut output = Vec::new();
+        assert!(encode_value_with_depth_limit(&registry, id, &value, &mut output, 2).is_ok());
+        let err = encode_value_with_depth_limit(&registry, id, &value, &mut output, 1);
+        assert_eq!(
+            err.unwrap_err().kind,
+            EncodeErrorKind::DepthLimitExceeded(1)
+        );
+    }
+
+    #[test]
+    fn lengths_out_of_range() {
+        let mut output = Vec::new();
+        assert!(encode_len(u32::MAX as usize, &mut output).is_ok());
+        if let Some(len) = (u32::MAX as usize).checked_add(1) {
+            assert_eq!(
+                encode_len(len, &mut output).unwrap_err().kind,
+                EncodeErrorKind::LengthOutOfRange(len)
+            );
+        }
+    }
+}
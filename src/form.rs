@@ -26,10 +26,14 @@
 //! can no longer be used to retrieve information from the
 //! original registry. Its sole purpose is for space-efficient serialization.
 //!
+//! The `PortableFormRef` is the same representation with strings borrowed
+//! from the bytes it was decoded from, see
+//! [`PortableRegistryRef`](crate::PortableRegistryRef).
+//!
 //! Other forms, such as a portable form that is still bound to the registry
 //! (also via lifetime tracking) are possible but current not needed.
 
-use crate::prelude::{any::TypeId, fmt::Debug};
+use crate::prelude::{any::TypeId, fmt::Debug, marker::PhantomData};
 
 use crate::{interner::UntrackedSymbol, meta_type::MetaType};
 
@@ -98,6 +102,20 @@ cfg_if::cfg_if! {
     }
 }
 
+/// Portable form borrowing its strings from SCALE encoded bytes.
+///
+/// Used by [`PortableRegistryRef`](crate::PortableRegistryRef) to decode a registry
+/// without allocating its names and docs.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct PortableFormRef<'a>(PhantomData<&'a ()>);
+
+impl<'a> Form for PortableFormRef<'a> {
+    type Type = UntrackedSymbol<TypeId>;
+    type String = &'a str;
+}
+
 // Blanket implementations
 #[cfg(not(feature = "schema"))]
 impl<T> JsonSchemaMaybe for T {}
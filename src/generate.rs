@@ -0,0 +1,568 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of random [`Value`]s of registry types, e.g. for fuzzing decoders.
+//!
+//! Generation is deterministic: a [`ValueGenerator`] created with the same seed generates the
+//! same values for the same registry. Variants are encoded with their
+//! [`index`](crate::Variant::index), and recursive types are kept within a maximum depth by
+//! only choosing the variants and sequence lengths which let the value end soon enough.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{generate::ValueGenerator, MetaType, PortableRegistry, Registry};
+//! # use scale::Decode;
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<Vec<Option<(u8, bool)>>>()).id;
+//! let registry: PortableRegistry = registry.into();
+//!
+//! let mut generator = ValueGenerator::new(&registry, 42).sequence_len(1, 4);
+//! for _ in 0..10 {
+//!     let encoded = generator.encoded(id).unwrap();
+//!     let decoded = Vec::<Option<(u8, bool)>>::decode(&mut &encoded[..]).unwrap();
+//!     assert!((1..=4).contains(&decoded.len()));
+//! }
+//! ```
+
+use crate::prelude::{
+    boxed::Box,
+    cmp,
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    encode::{encode_value, EncodeError},
+    form::PortableForm,
+    value::{Composite, Primitive, Value, VariantValue},
+    Field, PortableRegistry, TypeDef, TypeDefPrimitive,
+};
+
+/// An error that may be encountered when generating a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The type id could not be found in the registry.
+    TypeNotFound(u32),
+    /// The type with the given id has no values, e.g. an enum without variants, or only values
+    /// which refer to themselves without end.
+    NoValue(u32),
+    /// The generated value could not be encoded, e.g. because the type contains an invalid
+    /// compact or bit sequence type.
+    Encode(EncodeError),
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeNotFound(id) => write!(f, "type with id {id} not found in the registry"),
+            Self::NoValue(id) => write!(f, "type with id {id} has no finite values"),
+            Self::Encode(err) => write!(f, "failed to encode the generated value: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GenerateError {}
+
+/// Generates random values of the types of a registry from a seed.
+#[derive(Clone, Debug)]
+pub struct ValueGenerator<'a> {
+    registry: &'a PortableRegistry,
+    rng: SplitMix64,
+    min_len: usize,
+    max_len: usize,
+    max_depth: usize,
+    /// The depth of the shallowest value of each type, `None` if it has no values.
+    min_depths: Vec<Option<usize>>,
+}
+
+impl<'a> ValueGenerator<'a> {
+    /// Creates a generator for the types of the given registry, seeded with the given seed.
+    ///
+    /// By default sequences, strings and bit sequences have up to 8 items, and values are kept
+    /// within 8 levels of nesting where the type allows it.
+    pub fn new(registry: &'a PortableRegistry, seed: u64) -> Self {
+        Self {
+            registry,
+            rng: SplitMix64(seed),
+            min_len: 0,
+            max_len: 8,
+            max_depth: 8,
+            min_depths: min_depths(registry),
+        }
+    }
+
+    /// Sets the minimum and maximum number of items of the generated sequences, strings and
+    /// bit sequences.
+    ///
+    /// Sequences are left empty where their items would exceed the maximum depth.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is greater than `max`, or if `max` is greater than `u32::MAX`, the largest
+    /// length which can be encoded.
+    pub fn sequence_len(mut self, min: usize, max: usize) -> Self {
+        assert!(
+            min <= max,
+            "the minimum length must not exceed the maximum length"
+        );
+        assert!(
+            max as u64 <= u64::from(u32::MAX),
+            "the maximum length must not exceed u32::MAX"
+        );
+        self.min_len = min;
+        self.max_len = max;
+        self
+    }
+
+    /// Sets the depth beyond which values are made as shallow as possible, by choosing the
+    /// variants which end soonest and leaving sequences empty.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Generates a random value of the type with the given id.
+    ///
+    /// The value is shaped after the type but not checked against it; use
+    /// [`encoded`](Self::encoded) to also validate compact and bit sequence types.
+    pub fn value(&mut self, type_id: u32) -> Result<Value, GenerateError> {
+        self.generate(type_id, 0)
+    }
+
+    /// Generates the SCALE encoding of a random value of the type with the given id.
+    pub fn encoded(&mut self, type_id: u32) -> Result<Vec<u8>, GenerateError> {
+        let value = self.value(type_id)?;
+        let mut encoded = Vec::new();
+        encode_value(self.registry, type_id, &value, &mut encoded)
+            .map_err(GenerateError::Encode)?;
+        Ok(encoded)
+    }
+
+    fn generate(&mut self, type_id: u32, depth: usize) -> Result<Value, GenerateError> {
+        let registry = self.registry;
+        let ty = registry
+            .resolve(type_id)
+            .ok_or(GenerateError::TypeNotFound(type_id))?;
+        if self.min_depth(type_id).is_none() {
+            return Err(GenerateError::NoValue(type_id));
+        }
+
+        let value = match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                Value::Composite(self.fields(&composite.fields, depth)?)
+            }
+            TypeDef::Variant(variant) => {
+                // Only choose among the variants which end within the maximum depth, or the
+                // shallowest ones when none do.
+                let depths: Vec<_> = variant
+                    .variants
+                    .iter()
+                    .map(|variant| self.fields_depth(&variant.fields))
+                    .collect();
+                let shallowest = depths.iter().flatten().min().copied();
+                let allowed = |d: &Option<usize>| match d {
+                    Some(d) => depth + d <= self.max_depth || Some(*d) == shallowest,
+                    None => false,
+                };
+                let candidates: Vec<_> = variant
+                    .variants
+                    .iter()
+                    .zip(&depths)
+                    .filter(|(_, d)| allowed(d))
+                    .map(|(variant, _)| variant)
+                    .collect();
+                let variant = candidates[self.rng.below(candidates.len())];
+                let name: &str = variant.name.as_ref();
+                Value::Variant(VariantValue {
+                    name: name.to_string(),
+                    index: variant.index,
+                    fields: self.fields(&variant.fields, depth)?,
+                })
+            }
+            TypeDef::Sequence(sequence) => {
+                let item = sequence.type_param.id;
+                let len = match self.min_depth(item) {
+                    Some(d) if depth + 1 + d <= self.max_depth => self.len(),
+                    _ => 0,
+                };
+                Value::Sequence(self.items(item, len, depth)?)
+            }
+            TypeDef::Array(array) => {
+                let items = self.items(array.type_param.id, array.len as usize, depth)?;
+                Value::Array(items)
+            }
+            TypeDef::Tuple(tuple) => Value::Tuple(
+                tuple
+                    .fields
+                    .iter()
+                    .map(|field| self.generate(field.id, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+            TypeDef::Primitive(primitive) => Value::Primitive(self.primitive(primitive)),
+            TypeDef::Compact(compact) => {
+                Value::Compact(Box::new(self.generate(compact.type_param.id, depth + 1)?))
+            }
+            TypeDef::BitSequence(_) => {
+                let len = self.len();
+                Value::BitSequence((0..len).map(|_| self.rng.next_u64() & 1 == 1).collect())
+            }
+        };
+        Ok(value)
+    }
+
+    fn fields(
+        &mut self,
+        fields: &[Field<PortableForm>],
+        depth: usize,
+    ) -> Result<Composite, GenerateError> {
+        let named = !fields.is_empty() && fields.iter().all(|f| f.name.is_some());
+        let mut values = Vec::with_capacity(fields.len());
+        for field in fields {
+            values.push(self.generate(field.ty.id, depth + 1)?);
+        }
+        Ok(if named {
+            Composite::Named(
+                fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| {
+                        let name: &str =
+                            field.name.as_ref().expect("all fields are named").as_ref();
+                        (name.to_string(), value)
+                    })
+                    .collect(),
+            )
+        } else {
+            Composite::Unnamed(values)
+        })
+    }
+
+    fn items(
+        &mut self,
+        type_id: u32,
+        len: usize,
+        depth: usize,
+    ) -> Result<Vec<Value>, GenerateError> {
+        (0..len)
+            .map(|_| self.generate(type_id, depth + 1))
+            .collect()
+    }
+
+    fn primitive(&mut self, primitive: &TypeDefPrimitive) -> Primitive {
+        let bits = self.rng.next_u64();
+        match primitive {
+            TypeDefPrimitive::Bool => Primitive::Bool(bits & 1 == 1),
+            TypeDefPrimitive::Char => Primitive::Char(self.char()),
+            TypeDefPrimitive::Str => {
+                let len = self.len();
+                Primitive::Str((0..len).map(|_| self.char()).collect::<String>())
+            }
+            TypeDefPrimitive::U8 => Primitive::U8(bits as u8),
+            TypeDefPrimitive::U16 => Primitive::U16(bits as u16),
+            TypeDefPrimitive::U32 => Primitive::U32(bits as u32),
+            TypeDefPrimitive::U64 => Primitive::U64(bits),
+            TypeDefPrimitive::U128 => Primitive::U128(self.u128(bits)),
+            TypeDefPrimitive::U256 => Primitive::U256(self.bytes(bits)),
+            TypeDefPrimitive::I8 => Primitive::I8(bits as i8),
+            TypeDefPrimitive::I16 => Primitive::I16(bits as i16),
+            TypeDefPrimitive::I32 => Primitive::I32(bits as i32),
+            TypeDefPrimitive::I64 => Primitive::I64(bits as i64),
+            TypeDefPrimitive::I128 => Primitive::I128(self.u128(bits) as i128),
+            TypeDefPrimitive::I256 => Primitive::I256(self.bytes(bits)),
+        }
+    }
+
+    fn u128(&mut self, low: u64) -> u128 {
+        (u128::from(self.rng.next_u64()) << 64) | u128::from(low)
+    }
+
+    fn bytes(&mut self, first: u64) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&first.to_le_bytes());
+        for chunk in bytes[8..].chunks_mut(8) {
+            chunk.copy_from_slice(&self.rng.next_u64().to_le_bytes());
+        }
+        bytes
+    }
+
+    fn char(&mut self) -> char {
+        loop {
+            let code = self.rng.below(char::MAX as usize + 1) as u32;
+            // Surrogates are not valid chars.
+            if let Some(c) = char::from_u32(code) {
+                return c;
+            }
+        }
+    }
+
+    fn len(&mut self) -> usize {
+        match (self.max_len - self.min_len).checked_add(1) {
+            Some(lens) => self.min_len + self.rng.below(lens),
+            // Every `usize` is in range.
+            None => self.rng.next_u64() as usize,
+        }
+    }
+
+    fn min_depth(&self, type_id: u32) -> Option<usize> {
+        self.min_depths.get(type_id as usize).copied().flatten()
+    }
+
+    fn fields_depth(&self, fields: &[Field<PortableForm>]) -> Option<usize> {
+        fields.iter().try_fold(0, |depth, field| {
+            Some(cmp::max(depth, self.min_depth(field.ty.id)? + 1))
+        })
+    }
+}
+
+/// Returns the depth of the shallowest value of each type of the registry, `None` for the types
+/// without any value.
+///
+/// A primitive or an empty sequence has a depth of 0, and other values are one level deeper
+/// than their deepest field or item.
+fn min_depths(registry: &PortableRegistry) -> Vec<Option<usize>> {
+    let mut depths = crate::prelude::vec![None; registry.types.len()];
+    fn depth_of(depths: &[Option<usize>], id: u32) -> Option<usize> {
+        depths.get(id as usize).copied().flatten().map(|d| d + 1)
+    }
+    fn max_depth(depths: &[Option<usize>], mut ids: impl Iterator<Item = u32>) -> Option<usize> {
+        ids.try_fold(0, |max, id| Some(cmp::max(max, depth_of(depths, id)?)))
+    }
+    // Depths only ever decrease, from unknown to the depth of the shallowest value found so
+    // far, so this reaches a fixed point.
+    loop {
+        let mut changed = false;
+        for (id, ty) in registry.types.iter().enumerate() {
+            let depth = match &ty.ty.type_def {
+                TypeDef::Composite(composite) => {
+                    max_depth(&depths, composite.fields.iter().map(|f| f.ty.id))
+                }
+                TypeDef::Variant(variant) => variant
+                    .variants
+                    .iter()
+                    .filter_map(|v| max_depth(&depths, v.fields.iter().map(|f| f.ty.id)))
+                    .min(),
+                TypeDef::Sequence(_) | TypeDef::Primitive(_) | TypeDef::BitSequence(_) => Some(0),
+                TypeDef::Array(array) if array.len == 0 => Some(0),
+                TypeDef::Array(array) => depth_of(&depths, array.type_param.id),
+                TypeDef::Tuple(tuple) => max_depth(&depths, tuple.fields.iter().map(|f| f.id)),
+                TypeDef::Compact(compact) => depth_of(&depths, compact.type_param.id),
+            };
+            if depth.is_some() && depth != depths[id] {
+                depths[id] = depth;
+                changed = true;
+            }
+        }
+        if !changed {
+            return depths;
+        }
+    }
+}
+
+/// A small, fast and seedable pseudo random number generator.
+#[derive(Clone, Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number lower than `n`, which must not be zero.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode::decode_value, *};
+    use scale::{Compact, Decode, Encode};
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Decode, Encode, Debug)]
+    enum Call {
+        #[codec(index = 7)]
+        Transfer {
+            to: [u8; 32],
+            #[codec(compact)]
+            amount: u128,
+        },
+        #[codec(index = 3)]
+        Batch(Vec<Call>),
+        Remark(String, Option<u16>),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Decode, Encode, Debug)]
+    struct Tree {
+        value: (i64, Compact<u32>),
+        children: Vec<Tree>,
+    }
+
+    fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        (id, registry.into())
+    }
+
+    #[test]
+    fn generated_values_decode() {
+        let (id, registry) = registry_with::<Call>();
+        let mut generator = ValueGenerator::new(&registry, 1).max_depth(6);
+        let mut indices = Vec::new();
+        for _ in 0..200 {
+            let encoded = generator.encoded(id).unwrap();
+            indices.push(encoded[0]);
+            let input = &mut &encoded[..];
+            Call::decode(input).unwrap();
+            assert!(input.is_empty());
+
+            let input = &mut &encoded[..];
+            let decoded = decode_value(&registry, id, input).unwrap();
+            assert!(input.is_empty());
+            let mut reencoded = Vec::new();
+            encode_value(&registry, id, &decoded, &mut reencoded).unwrap();
+            assert_eq!(reencoded, encoded);
+        }
+        for index in [7, 3, 2] {
+            assert!(indices.contains(&index));
+        }
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        let (id, registry) = registry_with::<Tree>();
+        let generate = |seed| {
+            let mut generator = ValueGenerator::new(&registry, seed);
+            (0..10)
+                .map(|_| generator.encoded(id).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(generate(5), generate(5));
+        assert_ne!(generate(5), generate(6));
+    }
+
+    #[test]
+    fn recursion_and_lengths_are_bounded() {
+        fn depth(tree: &Tree) -> usize {
+            tree.children
+                .iter()
+                .map(|c| depth(c) + 1)
+                .max()
+                .unwrap_or(0)
+        }
+
+        let (id, registry) = registry_with::<Tree>();
+        let mut generator = ValueGenerator::new(&registry, 9)
+            .sequence_len(2, 3)
+            .max_depth(8);
+        for _ in 0..20 {
+            let encoded = generator.encoded(id).unwrap();
+            let tree = Tree::decode(&mut &encoded[..]).unwrap();
+            // Each level of the tree nests a `Vec` and its `Tree` items.
+            assert!(depth(&tree) <= 4);
+            assert!(tree.children.is_empty() || (2..=3).contains(&tree.children.len()));
+        }
+
+        let (id, registry) = registry_with::<Vec<u8>>();
+        let mut generator = ValueGenerator::new(&registry, 3).sequence_len(4, 4);
+        let encoded = generator.encoded(id).unwrap();
+        assert_eq!(Vec::<u8>::decode(&mut &encoded[..]).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn lengths_up_to_u32_max() {
+        let (id, registry) = registry_with::<Vec<u8>>();
+        // Sequences are left empty at the maximum depth, whatever their length.
+        let mut generator = ValueGenerator::new(&registry, 1)
+            .sequence_len(0, u32::MAX as usize)
+            .max_depth(0);
+        assert_eq!(generator.encoded(id), Ok(crate::prelude::vec![0]));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    #[should_panic(expected = "the maximum length must not exceed u32::MAX")]
+    fn lengths_above_u32_max() {
+        let (_, registry) = registry_with::<Vec<u8>>();
+        let _ = ValueGenerator::new(&registry, 1).sequence_len(0, u32::MAX as usize + 1);
+    }
+
+    #[test]
+    fn generate_bit_sequences() {
+        let mut builder = PortableRegistryBuilder::new();
+        let u16_id = builder.register_type(Type::new(
+            Path::default(),
+            crate::prelude::vec![],
+            TypeDefPrimitive::U16,
+            crate::prelude::vec![],
+        ));
+        let lsb0_id = builder.register_type(
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked(["Lsb0".into()]))
+                .composite(build::Fields::unit()),
+        );
+        let bits_id = builder.register_type(Type::new(
+            Path::default(),
+            crate::prelude::vec![],
+            TypeDefBitSequence::new_portable(u16_id.into(), lsb0_id.into()),
+            crate::prelude::vec![],
+        ));
+        let registry = builder.finish();
+
+        let mut generator = ValueGenerator::new(&registry, 0).sequence_len(1, 40);
+        for _ in 0..20 {
+            let encoded = generator.encoded(bits_id).unwrap();
+            let input = &mut &encoded[..];
+            let len = Compact::<u32>::decode(input).unwrap().0 as usize;
+            assert_eq!(input.len(), (len + 15) / 16 * 2);
+        }
+    }
+
+    #[test]
+    fn types_without_values() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Never {}
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Endless(Box<Endless>);
+
+        let (id, registry) = registry_with::<(Never, u8)>();
+        let never = match &registry.resolve(id).unwrap().type_def {
+            TypeDef::Tuple(tuple) => tuple.fields[0].id,
+            _ => unreachable!(),
+        };
+        let mut generator = ValueGenerator::new(&registry, 0);
+        assert_eq!(generator.value(id), Err(GenerateError::NoValue(id)));
+        assert_eq!(generator.value(never), Err(GenerateError::NoValue(never)));
+        assert_eq!(generator.value(100), Err(GenerateError::TypeNotFound(100)));
+
+        let (id, registry) = registry_with::<Option<Endless>>();
+        let mut generator = ValueGenerator::new(&registry, 0);
+        for _ in 0..10 {
+            assert_eq!(generator.encoded(id), Ok(crate::prelude::vec![0]));
+        }
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The graph of references between the types of a [`PortableRegistry`].
+//!
+//! A type references another type when the other type is one of its type parameters, the type
+//! of one of its fields or the item, element or store type of its definition. References to
+//! types missing from the registry are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{MetaType, PortableRegistry, Registry};
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<Option<Vec<u8>>>()).id;
+//! let registry: PortableRegistry = registry.into();
+//! let graph = registry.type_graph();
+//!
+//! // `Option<Vec<u8>>` refers to `Vec<u8>`, which refers to `u8`.
+//! assert_eq!(graph.closure(id).len(), 3);
+//! assert_eq!(graph.topological_order().last(), Some(&id));
+//! assert!(graph.recursive_types().is_empty());
+//! ```
+
+use crate::prelude::{
+    collections::{BTreeMap, BTreeSet},
+    vec,
+    vec::Vec,
+};
+
+use crate::{utils::type_refs, PortableRegistry};
+
+impl PortableRegistry {
+    /// Returns the graph of references between the types of the registry.
+    pub fn type_graph(&self) -> TypeGraph {
+        TypeGraph::new(self)
+    }
+}
+
+/// The references between the types of a [`PortableRegistry`], in both directions.
+///
+/// Types are identified by their id, i.e. their position in the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeGraph {
+    references: Vec<Vec<u32>>,
+    referenced_by: Vec<Vec<u32>>,
+}
+
+impl TypeGraph {
+    /// Builds the graph of references between the types of the registry.
+    pub fn new(registry: &PortableRegistry) -> Self {
+        let len = registry.types.len();
+        let mut references = Vec::with_capacity(len);
+        let mut referenced_by = vec![Vec::new(); len];
+        for (id, ty) in registry.types.iter().enumerate() {
+            let mut refs: Vec<u32> = type_refs(&ty.ty)
+                .into_iter()
+                .filter(|id| (*id as usize) < len)
+                .collect();
+            refs.sort_unstable();
+            refs.dedup();
+            for referenced in &refs {
+                referenced_by[*referenced as usize].push(id as u32);
+            }
+            references.push(refs);
+        }
+        Self {
+            references,
+            referenced_by,
+        }
+    }
+
+    /// Returns the number of types in the graph.
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Returns `true` if the graph contains no types.
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// Returns the ids of the types directly referenced by the given type, in ascending order.
+    pub fn references(&self, id: u32) -> &[u32] {
+        self.references
+            .get(id as usize)
+            .map_or(&[], |refs| &refs[..])
+    }
+
+    /// Returns the ids of the types directly referencing the given type, in ascending order.
+    pub fn referenced_by(&self, id: u32) -> &[u32] {
+        self.referenced_by
+            .get(id as usize)
+            .map_or(&[], |refs| &refs[..])
+    }
+
+    /// Returns the ids of the given type and of all the types it references, directly or not.
+    ///
+    /// These are the types which [`PortableRegistry::retain`] keeps for the given type.
+    pub fn closure(&self, id: u32) -> BTreeSet<u32> {
+        self.walk(id, |id| self.references(id))
+    }
+
+    /// Returns the ids of the given type and of all the types referencing it, directly or not,
+    /// i.e. the types affected by a change to the given type.
+    pub fn reverse_closure(&self, id: u32) -> BTreeSet<u32> {
+        self.walk(id, |id| self.referenced_by(id))
+    }
+
+    /// Returns the ids of the types which reference themselves, directly or not.
+    pub fn recursive_types(&self) -> BTreeSet<u32> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| match component[..] {
+                [id] => self.references(id).contains(&id),
+                _ => true,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the groups of mutually recursive types, each type in its own group if it is not
+    /// recursive.
+    ///
+    /// Groups come after the groups of all the types they reference, and each group is sorted.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<u32>> {
+        let indices = self.component_indices();
+        let mut components = vec![Vec::new(); indices.iter().max().map_or(0, |max| max + 1)];
+        for (id, component) in indices.into_iter().enumerate() {
+            components[component].push(id as u32);
+        }
+        components
+    }
+
+    /// Returns the ids of all the types, each type coming after the types it references.
+    ///
+    /// Mutually recursive types, which can't be ordered, are sorted by id.
+    pub fn topological_order(&self) -> Vec<u32> {
+        self.strongly_connected_components()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the index of the strongly connected component of each type. Components are
+    /// numbered after all the components they reference.
+    pub(crate) fn component_indices(&self) -> Vec<usize> {
+        let references = |node: usize| self.references[node].clone();
+        let mut components = ComponentIndices::new();
+        (0..self.len())
+            .map(|node| components.get(node, &references))
+            .collect()
+    }
+
+    fn walk<'a>(&'a self, id: u32, next: impl Fn(u32) -> &'a [u32]) -> BTreeSet<u32> {
+        let mut visited = BTreeSet::new();
+        if (id as usize) >= self.len() {
+            return visited;
+        }
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if visited.insert(id) {
+                stack.extend(next(id).iter().filter(|id| !visited.contains(id)));
+            }
+        }
+        visited
+    }
+}
+
+/// The strongly connected components of a graph, found with Tarjan's algorithm. Components are
+/// only computed for the nodes reachable from the nodes requested so far, so the nodes
+/// unrelated to them are never visited, nor is any state kept for them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ComponentIndices {
+    nodes: BTreeMap<usize, Node>,
+    stack: Vec<usize>,
+    /// The nodes of each component found so far.
+    members: Vec<Vec<usize>>,
+}
+
+/// The state of a node visited by [`ComponentIndices`].
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    index: usize,
+    /// `None` while the node is on the stack.
+    component: Option<usize>,
+}
+
+impl ComponentIndices {
+    /// Creates the components of a graph, before any node is visited.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of the component of the given node, given the nodes referenced by
+    /// each node. Components are numbered after all the components they reference.
+    pub(crate) fn get(&mut self, node: usize, references: &impl Fn(usize) -> Vec<u32>) -> usize {
+        if !self.nodes.contains_key(&node) {
+            self.visit(node, references);
+        }
+        self.nodes[&node]
+            .component
+            .expect("visited nodes are assigned a component once the visit is done")
+    }
+
+    /// Returns the number of components found so far. Components found by the next call to
+    /// [`get`](Self::get) are numbered from this number onwards.
+    pub(crate) fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns the nodes of a component returned by [`get`](Self::get), in ascending order.
+    pub(crate) fn members(&self, component: usize) -> &[usize] {
+        &self.members[component]
+    }
+
+    /// Visits a node which hasn't been visited yet, along with all the nodes reachable from it.
+    ///
+    /// The depth first search keeps its own stack of nodes being visited, so that long chains of
+    /// references can't overflow the call stack.
+    fn visit(&mut self, node: usize, references: &impl Fn(usize) -> Vec<u32>) {
+        let mut visiting = vec![self.enter(node, references)];
+        while let Some(frame) = visiting.last_mut() {
+            if let Some(next) = frame.references.get(frame.next).map(|id| *id as usize) {
+                frame.next += 1;
+                match self.nodes.get(&next) {
+                    None => {
+                        let frame = self.enter(next, references);
+                        visiting.push(frame);
+                    }
+                    Some(Node {
+                        index,
+                        component: None,
+                    }) => frame.low_link = frame.low_link.min(*index),
+                    Some(_) => (),
+                }
+                continue;
+            }
+
+            let frame = visiting.pop().expect("the stack is not empty; qed");
+            if let Some(parent) = visiting.last_mut() {
+                parent.low_link = parent.low_link.min(frame.low_link);
+            }
+            if frame.low_link == frame.index {
+                let component = self.members.len();
+                let mut members = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    if let Some(state) = self.nodes.get_mut(&member) {
+                        state.component = Some(component);
+                    }
+                    members.push(member);
+                    if member == frame.node {
+                        break;
+                    }
+                }
+                members.sort_unstable();
+                self.members.push(members);
+            }
+        }
+    }
+
+    /// Numbers a node and pushes it on the stack, returning the state of its visit.
+    fn enter(&mut self, node: usize, references: &impl Fn(usize) -> Vec<u32>) -> Frame {
+        let index = self.nodes.len();
+        self.nodes.insert(
+            node,
+            Node {
+                index,
+                component: None,
+            },
+        );
+        self.stack.push(node);
+        Frame {
+            node,
+            index,
+            low_link: index,
+            references: references(node),
+            next: 0,
+        }
+    }
+}
+
+/// A node being visited by [`ComponentIndices::visit`].
+struct Frame {
+    node: usize,
+    index: usize,
+    low_link: usize,
+    references: Vec<u32>,
+    /// The position of the next reference to follow.
+    next: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Expr {
+        Value(u32),
+        Add(Box<Expr>, Box<Expr>),
+        Block(Vec<Stmt>),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Stmt {
+        expr: Expr,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Root {
+        expr: Expr,
+        flag: bool,
+    }
+
+    #[test]
+    fn type_graph_queries() {
+        let mut registry = Registry::new();
+        let root = registry.register_type(&MetaType::new::<Root>()).id;
+        let registry: PortableRegistry = registry.into();
+        let graph = registry.type_graph();
+
+        let id_of = |ident: &str| {
+            registry
+                .types
+                .iter()
+                .find(|ty| ty.ty.path.ident().as_deref() == Some(ident))
+                .map(|ty| ty.id)
+                .unwrap()
+        };
+        let (expr, stmt) = (id_of("Expr"), id_of("Stmt"));
+        let vec_stmt = match graph.referenced_by(stmt) {
+            [vec_stmt] => *vec_stmt,
+            other => panic!("`Stmt` is only referenced by `Vec<Stmt>`, got {other:?}"),
+        };
+
+        assert_eq!(graph.references(root).len(), 2);
+        assert_eq!(graph.references(vec_stmt), [stmt]);
+        assert!(graph.references(expr).contains(&vec_stmt));
+        assert_eq!(graph.closure(root).len(), registry.types.len());
+        assert_eq!(
+            graph.reverse_closure(stmt),
+            [root, expr, stmt, vec_stmt].into_iter().collect()
+        );
+        assert_eq!(
+            graph.recursive_types(),
+            [expr, stmt, vec_stmt].into_iter().collect()
+        );
+
+        let order = graph.topological_order();
+        assert_eq!(order.len(), registry.types.len());
+        assert_eq!(order.last(), Some(&root));
+        for (position, id) in order.iter().enumerate() {
+            let before = &order[..position];
+            let recursive = graph
+                .strongly_connected_components()
+                .into_iter()
+                .find(|component| component.contains(id))
+                .unwrap();
+            for referenced in graph.references(*id) {
+                assert!(before.contains(referenced) || recursive.contains(referenced));
+            }
+        }
+
+        assert!(graph.references(1000).is_empty());
+        assert!(graph.closure(1000).is_empty());
+    }
+
+    /// Returns a registry of `len` sequences, each of the next one, the last one being a
+    /// sequence of the first one if `cyclic`, or of a missing type otherwise.
+    fn chain(len: u32, cyclic: bool) -> PortableRegistry {
+        let last = if cyclic { 0 } else { len };
+        PortableRegistry {
+            types: (0..len)
+                .map(|id| {
+                    let next = if id + 1 == len { last } else { id + 1 };
+                    PortableType::new(id, TypeDefSequence::new(next.into()).into())
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn deep_chains() {
+        let len = 100_000;
+        // Run on a thread with a small stack, as a recursive search would overflow it.
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || {
+                let graph = chain(len, false).type_graph();
+                assert_eq!(
+                    graph.topological_order(),
+                    (0..len).rev().collect::<Vec<_>>()
+                );
+                assert!(graph.recursive_types().is_empty());
+
+                let graph = chain(len, true).type_graph();
+                assert_eq!(
+                    graph.strongly_connected_components(),
+                    [(0..len).collect::<Vec<_>>()]
+                );
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
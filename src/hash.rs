@@ -0,0 +1,562 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural hashing of the types of a [`PortableRegistry`].
+//!
+//! The hash of a type only depends on its structure and that of the types it refers to, never
+//! on type ids, so the same type hashes identically in any registry. Mutually recursive types
+//! are hashed as a group: each type of the group is hashed along with the types of the group it
+//! reaches, in the order they are first referred to, and references within the group are
+//! hashed as positions in that order instead of descending again.
+//!
+//! The hash is computed with 128 bit FNV-1a over a canonical serialization of the type, and is
+//! stable across platforms.
+
+use crate::prelude::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    form::PortableForm, graph::ComponentIndices, utils::type_refs, Field, PortableRegistry, Type,
+    TypeDef, TypeDefPrimitive, TypeParameter,
+};
+
+/// Selects which parts of a type contribute to its hash.
+///
+/// The default options only hash the encoding shape of a type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HashOptions {
+    /// Include the docs of types, fields and variants.
+    pub docs: bool,
+    /// Include paths, type parameters, type names and the names of fields and variants.
+    pub names: bool,
+}
+
+impl HashOptions {
+    /// Only hash what affects the SCALE encoding of values of the type.
+    pub fn shape() -> Self {
+        Self {
+            docs: false,
+            names: false,
+        }
+    }
+
+    /// Hash all of the metadata of the type.
+    pub fn full() -> Self {
+        Self {
+            docs: true,
+            names: true,
+        }
+    }
+}
+
+impl PortableRegistry {
+    /// Returns the structural hash of the type with the given id, or `None` if the type, or any
+    /// type it refers to, could not be found.
+    ///
+    /// Only the types reachable from the type are visited. Use a [`TypeHasher`] to hash many
+    /// types of the same registry, reusing the hashes of shared types.
+    pub fn type_hash(&self, id: u32, options: HashOptions) -> Option<u128> {
+        TypeHasher::new(self, options).hash(id)
+    }
+}
+
+/// Computes the structural hashes of the types of a registry, caching the hash of every type
+/// visited along the way.
+#[derive(Debug)]
+pub struct TypeHasher<'a> {
+    registry: &'a PortableRegistry,
+    options: HashOptions,
+    /// The hash of every type visited, `None` for the types which can't be hashed.
+    cache: BTreeMap<u32, Option<u128>>,
+    /// The strongly connected component of each type, i.e. the group of mutually recursive
+    /// types it belongs to, computed for the types reachable from the hashed types only.
+    components: ComponentIndices,
+}
+
+/// The types of a group of mutually recursive types reached while hashing one of them.
+struct Group {
+    component: usize,
+    /// The types of the group in the order they are first referred to, starting with the
+    /// hashed type.
+    order: Vec<u32>,
+}
+
+impl<'a> TypeHasher<'a> {
+    /// Creates a hasher for the types of the given registry.
+    pub fn new(registry: &'a PortableRegistry, options: HashOptions) -> Self {
+        Self {
+            registry,
+            options,
+            cache: BTreeMap::new(),
+            components: ComponentIndices::new(),
+        }
+    }
+
+    /// Returns the structural hash of the type with the given id, or `None` if the type, or
+    /// any type it refers to, could not be found.
+    pub fn hash(&mut self, id: u32) -> Option<u128> {
+        if let Some(hash) = self.cache.get(&id) {
+            return *hash;
+        }
+        self.registry.resolve(id)?;
+        // Groups are numbered after the groups they refer to, so hashing the groups found along
+        // the way in order means the types referred to outside of a group are already hashed.
+        let first = self.components.len();
+        let component = self.component(id);
+        for component in first..=component {
+            self.hash_group(component);
+        }
+        self.cache.get(&id).copied().flatten()
+    }
+
+    /// Hashes and caches every type of a group of mutually recursive types. The hash of every
+    /// type of the group only depends on the types it reaches, so the whole group can be
+    /// hashed at once.
+    fn hash_group(&mut self, component: usize) {
+        let members = self.components.members(component).to_vec();
+        let hashes: Option<Vec<u128>> = members
+            .iter()
+            .map(|member| self.hash_in_group(*member as u32, component))
+            .collect();
+        for (position, member) in members.into_iter().enumerate() {
+            let hash = hashes.as_ref().map(|hashes| hashes[position]);
+            self.cache.insert(member as u32, hash);
+        }
+    }
+
+    /// Returns the strongly connected component of a type of the registry.
+    fn component(&mut self, id: u32) -> usize {
+        let registry = self.registry;
+        let references = |node: usize| {
+            let mut refs = type_refs(&registry.types[node].ty);
+            refs.retain(|id| (*id as usize) < registry.types.len());
+            refs
+        };
+        self.components.get(id as usize, &references)
+    }
+
+    /// Hashes a type along with the types of its group it reaches.
+    fn hash_in_group(&mut self, id: u32, component: usize) -> Option<u128> {
+        let mut state = State::new();
+        let mut group = Group {
+            component,
+            order: crate::prelude::vec![id],
+        };
+        let mut position = 0;
+        while let Some(id) = group.order.get(position) {
+            let ty = self.registry.resolve(*id)?;
+            self.write_type(&mut state, &mut group, ty)?;
+            position += 1;
+        }
+        Some(state.0)
+    }
+
+    /// Writes a reference to another type, either as its position in the group or as its hash.
+    fn write_ref(&mut self, state: &mut State, group: &mut Group, id: u32) -> Option<()> {
+        let in_group =
+            (id as usize) < self.registry.types.len() && self.component(id) == group.component;
+        if in_group {
+            let position = match group.order.iter().position(|member| *member == id) {
+                Some(position) => position,
+                None => {
+                    group.order.push(id);
+                    group.order.len() - 1
+                }
+            };
+            state.write_u8(tag::GROUP_REF);
+            state.write_len(position);
+        } else {
+            let hash = self.cache.get(&id).copied().flatten()?;
+            state.write_u8(tag::REF);
+            state.write(&hash.to_le_bytes());
+        }
+        Some(())
+    }
+
+    fn write_type(
+        &mut self,
+        state: &mut State,
+        group: &mut Group,
+        ty: &Type<PortableForm>,
+    ) -> Option<()> {
+        if self.options.names {
+            state.write_strs(&ty.path.segments);
+            self.write_type_params(state, group, &ty.type_params)?;
+        }
+        if self.options.docs {
+            state.write_strs(&ty.docs);
+        }
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                state.write_u8(tag::COMPOSITE);
+                self.write_fields(state, group, &composite.fields)?;
+            }
+            TypeDef::Variant(variant) => {
+                state.write_u8(tag::VARIANT);
+                state.write_len(variant.variants.len());
+                for variant in &variant.variants {
+                    state.write_u8(variant.index);
+                    if self.options.names {
+                        state.write_str(&variant.name);
+                    }
+                    if self.options.docs {
+                        state.write_strs(&variant.docs);
+                    }
+                    self.write_fields(state, group, &variant.fields)?;
+                }
+            }
+            TypeDef::Sequence(sequence) => {
+                state.write_u8(tag::SEQUENCE);
+                self.write_ref(state, group, sequence.type_param.id)?;
+            }
+            TypeDef::Array(array) => {
+                state.write_u8(tag::ARRAY);
+                state.write(&array.len.to_le_bytes());
+                self.write_ref(state, group, array.type_param.id)?;
+            }
+            TypeDef::Tuple(tuple) => {
+                state.write_u8(tag::TUPLE);
+                state.write_len(tuple.fields.len());
+                for ty in &tuple.fields {
+                    self.write_ref(state, group, ty.id)?;
+                }
+            }
+            TypeDef::Primitive(primitive) => {
+                state.write_u8(tag::PRIMITIVE);
+                state.write_u8(primitive_tag(primitive));
+            }
+            TypeDef::Compact(compact) => {
+                state.write_u8(tag::COMPACT);
+                self.write_ref(state, group, compact.type_param.id)?;
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                state.write_u8(tag::BIT_SEQUENCE);
+                self.write_ref(state, group, bit_sequence.bit_store_type.id)?;
+                // The bit order is only identified by its name, which affects the encoding.
+                let order = self.registry.resolve(bit_sequence.bit_order_type.id)?;
+                state.write_strs(&order.path.segments);
+            }
+        }
+        Some(())
+    }
+
+    fn write_type_params(
+        &mut self,
+        state: &mut State,
+        group: &mut Group,
+        params: &[TypeParameter<PortableForm>],
+    ) -> Option<()> {
+        state.write_len(params.len());
+        for param in params {
+            state.write_str(&param.name);
+            match &param.ty {
+                Some(ty) => self.write_ref(state, group, ty.id)?,
+                None => state.write_u8(tag::NONE),
+            }
+        }
+        Some(())
+    }
+
+    fn write_fields(
+        &mut self,
+        state: &mut State,
+        group: &mut Group,
+        fields: &[Field<PortableForm>],
+    ) -> Option<()> {
+        state.write_len(fields.len());
+        for field in fields {
+            if self.options.names {
+                state.write_opt_str(field.name.as_ref());
+                state.write_opt_str(field.type_name.as_ref());
+            }
+            if self.options.docs {
+                state.write_strs(&field.docs);
+            }
+            self.write_ref(state, group, field.ty.id)?;
+        }
+        Some(())
+    }
+}
+
+/// Tags written before each part of the canonical serialization, so that different
+/// structures can never serialize to the same bytes.
+mod tag {
+    pub const COMPOSITE: u8 = 0;
+    pub const VARIANT: u8 = 1;
+    pub const SEQUENCE: u8 = 2;
+    pub const ARRAY: u8 = 3;
+    pub const TUPLE: u8 = 4;
+    pub const PRIMITIVE: u8 = 5;
+    pub const COMPACT: u8 = 6;
+    pub const BIT_SEQUENCE: u8 = 7;
+    pub const NONE: u8 = 0xFD;
+    pub const REF: u8 = 0xFE;
+    pub const GROUP_REF: u8 = 0xFF;
+}
+
+fn primitive_tag(primitive: &TypeDefPrimitive) -> u8 {
+    match primitive {
+        TypeDefPrimitive::Bool => 0,
+        TypeDefPrimitive::Char => 1,
+        TypeDefPrimitive::Str => 2,
+        TypeDefPrimitive::U8 => 3,
+        TypeDefPrimitive::U16 => 4,
+        TypeDefPrimitive::U32 => 5,
+        TypeDefPrimitive::U64 => 6,
+        TypeDefPrimitive::U128 => 7,
+        TypeDefPrimitive::U256 => 8,
+        TypeDefPrimitive::I8 => 9,
+        TypeDefPrimitive::I16 => 10,
+        TypeDefPrimitive::I32 => 11,
+        TypeDefPrimitive::I64 => 12,
+        TypeDefPrimitive::I128 => 13,
+        TypeDefPrimitive::I256 => 14,
+    }
+}
+
+/// The state of a 128 bit FNV-1a hash.
+struct State(u128);
+
+impl State {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u128::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, byte: u8) {
+        self.write(&[byte])
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes())
+    }
+
+    fn write_str<S: AsRef<str>>(&mut self, s: &S) {
+        let s = s.as_ref();
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn write_opt_str<S: AsRef<str>>(&mut self, s: Option<&S>) {
+        match s {
+            Some(s) => {
+                self.write_u8(1);
+                self.write_str(s);
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    fn write_strs<S: AsRef<str>>(&mut self, strs: &[S]) {
+        self.write_len(strs.len());
+        for s in strs {
+            self.write_str(s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Recursive {
+        Value(Box<Recursive>),
+        Empty,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Tree {
+        value: u32,
+        children: Vec<Tree>,
+        parent: Option<Box<Tree>>,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Renamed {
+        number: u32,
+        children: Vec<Renamed>,
+        parent: Option<Box<Renamed>>,
+    }
+
+    fn registry_with(types: impl IntoIterator<Item = MetaType>) -> (Vec<u32>, PortableRegistry) {
+        let mut registry = Registry::new();
+        let ids = types
+            .into_iter()
+            .map(|ty| registry.register_type(&ty).id)
+            .collect();
+        (ids, registry.into())
+    }
+
+    #[test]
+    fn hash_is_independent_of_ids() {
+        let (a_ids, a) = registry_with([MetaType::new::<Recursive>(), MetaType::new::<Tree>()]);
+        let (b_ids, b) = registry_with([
+            MetaType::new::<bool>(),
+            MetaType::new::<Tree>(),
+            MetaType::new::<String>(),
+            MetaType::new::<Recursive>(),
+        ]);
+        assert_ne!(a_ids, vec![b_ids[3], b_ids[1]]);
+
+        for options in [HashOptions::shape(), HashOptions::full()] {
+            assert_eq!(
+                a.type_hash(a_ids[0], options),
+                b.type_hash(b_ids[3], options)
+            );
+            assert_eq!(
+                a.type_hash(a_ids[1], options),
+                b.type_hash(b_ids[1], options)
+            );
+            assert_ne!(
+                a.type_hash(a_ids[0], options),
+                a.type_hash(a_ids[1], options)
+            );
+        }
+    }
+
+    #[test]
+    fn hash_of_recursive_types_does_not_depend_on_entry_point() {
+        let (ids, registry) = registry_with([MetaType::new::<Option<Box<Tree>>>()]);
+        let tree_id = registry
+            .types
+            .iter()
+            .find(|ty| ty.ty.path.ident() == Some("Tree".into()))
+            .unwrap()
+            .id;
+
+        // Hashing the option first caches nothing inside the cycle, so the tree must hash the
+        // same as when hashed on its own.
+        let mut hasher = TypeHasher::new(&registry, HashOptions::full());
+        hasher.hash(ids[0]).unwrap();
+        assert_eq!(
+            hasher.hash(tree_id),
+            registry.type_hash(tree_id, HashOptions::full())
+        );
+    }
+
+    #[test]
+    fn options_select_hashed_metadata() {
+        let (ids, registry) = registry_with([
+            MetaType::new::<Tree>(),
+            MetaType::new::<Renamed>(),
+            MetaType::new::<u64>(),
+            MetaType::new::<(u64,)>(),
+        ]);
+
+        let hash = |id, options| registry.type_hash(id, options).unwrap();
+        assert_eq!(
+            hash(ids[0], HashOptions::shape()),
+            hash(ids[1], HashOptions::shape())
+        );
+        assert_ne!(
+            hash(ids[0], HashOptions::full()),
+            hash(ids[1], HashOptions::full())
+        );
+
+        let mut documented = registry.clone();
+        documented.types[ids[0] as usize].ty.docs = vec!["A tree.".into()];
+        let docs = HashOptions {
+            docs: true,
+            names: false,
+        };
+        assert_eq!(
+            documented.type_hash(ids[0], HashOptions::shape()),
+            registry.type_hash(ids[0], HashOptions::shape())
+        );
+        assert_ne!(
+            documented.type_hash(ids[0], docs),
+            registry.type_hash(ids[0], docs)
+        );
+        // Wrapping a type changes its shape.
+        assert_ne!(
+            hash(ids[2], HashOptions::shape()),
+            hash(ids[3], HashOptions::shape())
+        );
+        assert_eq!(registry.type_hash(1000, HashOptions::shape()), None);
+    }
+
+    #[test]
+    fn dense_recursive_group() {
+        // A cycle of pairs each holding two of the next, so every type of the cycle reaches the
+        // others through exponentially many paths.
+        let cycle = |len: u32| PortableRegistry {
+            types: (0..len)
+                .map(|id| {
+                    let next = (id + 1) % len;
+                    let pair = TypeDefTuple::new_portable(vec![next.into(), next.into()]);
+                    PortableType::new(id, pair.into())
+                })
+                .collect(),
+        };
+        let registry = cycle(24);
+
+        // Hashing the types of the group in turn must not depend on what is already cached.
+        let mut hasher = TypeHasher::new(&registry, HashOptions::full());
+        let hashes: Vec<_> = (0..24).map(|id| hasher.hash(id).unwrap()).collect();
+        assert!(hashes.iter().all(|hash| *hash == hashes[0]));
+        assert_eq!(registry.type_hash(23, HashOptions::full()), Some(hashes[0]));
+        assert_ne!(cycle(23).type_hash(0, HashOptions::full()), Some(hashes[0]));
+    }
+
+    #[test]
+    fn deep_chain() {
+        // Sequences each of the next one, the last one being a sequence of `u8`, or of a missing
+        // type if `dangling`.
+        let chain = |len: u32, dangling: bool| {
+            let mut types: Vec<_> = (0..len)
+                .map(|id| PortableType::new(id, TypeDefSequence::new((id + 1).into()).into()))
+                .collect();
+            if !dangling {
+                types.push(PortableType::new(len, TypeDefPrimitive::U8.into()));
+            }
+            PortableRegistry { types }
+        };
+        // Run on a thread with a small stack, as hashing the chain recursively would overflow it.
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || {
+                let len = 100_000;
+                let registry = chain(len, false);
+                let mut hasher = TypeHasher::new(&registry, HashOptions::shape());
+                assert_eq!(
+                    hasher.hash(1),
+                    chain(len - 1, false).type_hash(0, HashOptions::shape())
+                );
+                assert_ne!(hasher.hash(0), hasher.hash(1));
+
+                let registry = chain(len, true);
+                let mut hasher = TypeHasher::new(&registry, HashOptions::shape());
+                assert_eq!(hasher.hash(0), None);
+                assert_eq!(hasher.hash(len / 2), None);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
@@ -248,6 +248,82 @@
 //! }
 //! ```
 //!
+//! #### `#[scale_info(with = SomeType)]`
+//!
+//! Describe a field by the type information of `SomeType` instead of that of the type of the
+//! field, e.g. for a field of a foreign type which does not implement `TypeInfo` and is encoded
+//! like `SomeType`. The `type_name` of the field remains the name of its own type.
+//!
+//! Example:
+//! ```ignore
+//! #[derive(TypeInfo)]
+//! struct Account {
+//!     #[scale_info(with = [u8; 32])]
+//!     id: foreign::AccountId,
+//! }
+//! ```
+//!
+//! #### `#[scale_info(transparent)]`
+//!
+//! For a struct with a single field, e.g. a newtype, return the type information of the field
+//! instead of describing the struct itself, so that the struct has the same metadata as the
+//! type it wraps. Fields skipped with `#[codec(skip)]` are ignored. As the struct itself is not
+//! described, `transparent` can't be combined with `rename`, `rename_all`, `replace_segment`,
+//! `capture_docs` or `skip_type_params`, nor with a `rename` of the field.
+//!
+//! With `#[scale_info(transparent(identity))]` the `Identity` of the struct is also the type of
+//! its field, so both are registered as the same type in a [`Registry`].
+//!
+//! Example:
+//! ```ignore
+//! #[derive(TypeInfo)]
+//! #[scale_info(transparent)]
+//! struct Balance(u128);
+//! ```
+//!
+//! #### `#[scale_info(rename = "name")]`
+//!
+//! On a field or variant, use `name` as its name in place of the Rust identifier. On the type
+//! itself, use `name` as the last segment of its `path`.
+//!
+//! #### `#[scale_info(rename_all = "rule")]`
+//!
+//! Rename all the named fields of a struct, or all the variants of an enum, according to the
+//! given case convention, one of `"lowercase"`, `"UPPERCASE"`, `"PascalCase"`, `"camelCase"`,
+//! `"snake_case"`, `"SCREAMING_SNAKE_CASE"`, `"kebab-case"` or `"SCREAMING-KEBAB-CASE"`.
+//! As with serde, the fields of the variants of an enum keep their names. Fields and variants
+//! with a `rename` attribute keep the given name.
+//!
+//! Example:
+//! ```ignore
+//! #[derive(TypeInfo)]
+//! #[scale_info(rename = "Event", rename_all = "snake_case")]
+//! enum PalletEvent {
+//!     // Named `transfer_executed` in the metadata, its field keeps the name `amount_paid`.
+//!     TransferExecuted { amount_paid: u128 },
+//!     #[scale_info(rename = "burn")]
+//!     Burned,
+//! }
+//! ```
+//!
+//! #### `#[scale_info(skip)]`
+//!
+//! Leave a field or variant out of the type information, e.g. for types with a hand-written
+//! `Encode` implementation which should not expose deprecated or internal-only variants. Unlike
+//! `#[codec(skip)]`, a hidden variant is still encoded, so it keeps its place in the implicit
+//! variant indices and the remaining variants keep their indices. Hiding a variant is rejected
+//! if it is also skipped with `#[codec(skip)]`, or if two variants of the enum share an index.
+//!
+//! Example:
+//! ```ignore
+//! #[derive(TypeInfo)]
+//! enum Call {
+//!     #[scale_info(skip)]
+//!     Internal, // index 0, not in the type information
+//!     Transfer, // index 1
+//! }
+//! ```
+//!
 //! # Forms
 //!
 //! To bridge between compile-time type information and runtime the
@@ -355,21 +431,40 @@ macro_rules! type_params {
 pub mod prelude;
 
 pub mod build;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+pub mod compat;
+pub mod decode;
+pub mod diff;
+pub mod encode;
 pub mod form;
+pub mod generate;
+pub mod graph;
+pub mod hash;
 mod impls;
 pub mod interner;
+pub mod lookup;
 mod meta_type;
 mod portable;
+mod portable_lazy;
+mod portable_ref;
+pub mod pretty;
 mod registry;
+pub mod size;
+pub mod strip;
 mod ty;
 mod utils;
+pub mod validate;
+pub mod value;
 
 #[doc(hidden)]
 pub use scale;
 
 pub use self::{
     meta_type::MetaType,
-    portable::{PortableRegistry, PortableRegistryBuilder, PortableType},
+    portable::{MergeError, PortableRegistry, PortableRegistryBuilder, PortableType},
+    portable_lazy::LazyPortableRegistry,
+    portable_ref::{PortableRegistryRef, PortableTypeRef},
     registry::{IntoPortable, Registry},
     ty::*,
 };
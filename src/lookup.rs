@@ -0,0 +1,207 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lookup of the types of a [`PortableRegistry`] by their path.
+//!
+//! Several types may share a path, one for each instantiation of a generic type, so every
+//! lookup returns all the matching types, in the order of their ids, along with their type
+//! parameters. Types without a path, e.g. tuples or sequences, are not indexed.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{MetaType, PortableRegistry, Registry};
+//! let mut registry = Registry::new();
+//! registry.register_type(&MetaType::new::<(Option<u8>, Option<bool>)>());
+//! let registry: PortableRegistry = registry.into();
+//! let index = registry.type_index();
+//!
+//! let options = index.by_path(&["Option"]);
+//! assert_eq!(options.len(), 2);
+//! assert_eq!(options[0].type_params[0].name, "T");
+//! assert!(options[0].type_params[0].ty.is_some());
+//! ```
+
+use crate::prelude::{collections::BTreeMap, ops::Bound, vec::Vec};
+
+use crate::{form::PortableForm, PortableRegistry, Type};
+
+impl PortableRegistry {
+    /// Returns an index of the types of the registry by their path.
+    pub fn type_index(&self) -> TypeIndex<'_> {
+        TypeIndex::new(self)
+    }
+}
+
+/// An index of the types of a [`PortableRegistry`] by their path and identifier.
+#[derive(Clone, Debug)]
+pub struct TypeIndex<'a> {
+    registry: &'a PortableRegistry,
+    by_path: BTreeMap<Vec<&'a str>, Vec<u32>>,
+    by_ident: BTreeMap<&'a str, Vec<u32>>,
+}
+
+/// A type found in a [`TypeIndex`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeMatch<'a> {
+    /// The id of the type.
+    pub id: u32,
+    /// The type.
+    pub ty: &'a Type<PortableForm>,
+    /// The type parameters of the type, resolved in the registry.
+    pub type_params: Vec<ResolvedTypeParam<'a>>,
+}
+
+/// A type parameter of a [`TypeMatch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedTypeParam<'a> {
+    /// The name of the type parameter, e.g. `T`.
+    pub name: &'a str,
+    /// The id of the type the parameter is instantiated with, if any.
+    pub id: Option<u32>,
+    /// The type the parameter is instantiated with, `None` if it has no type or the type could
+    /// not be found.
+    pub ty: Option<&'a Type<PortableForm>>,
+}
+
+impl<'a> TypeIndex<'a> {
+    /// Indexes the types of the registry.
+    pub fn new(registry: &'a PortableRegistry) -> Self {
+        let mut by_path = BTreeMap::<_, Vec<_>>::new();
+        let mut by_ident = BTreeMap::<_, Vec<_>>::new();
+        for ty in registry.types.iter() {
+            let segments: Vec<&str> = ty.ty.path.segments.iter().map(AsRef::as_ref).collect();
+            if let Some(ident) = segments.last() {
+                by_ident.entry(*ident).or_default().push(ty.id);
+                by_path.entry(segments).or_default().push(ty.id);
+            }
+        }
+        Self {
+            registry,
+            by_path,
+            by_ident,
+        }
+    }
+
+    /// Returns the types with the given path, e.g. `["pallet_balances", "Call"]`.
+    pub fn by_path(&self, segments: &[&str]) -> Vec<TypeMatch<'a>> {
+        self.matches(self.by_path.get(segments).into_iter().flatten())
+    }
+
+    /// Returns the types with the given identifier, i.e. the last segment of their path, in any
+    /// namespace.
+    pub fn by_ident(&self, ident: &str) -> Vec<TypeMatch<'a>> {
+        self.matches(self.by_ident.get(ident).into_iter().flatten())
+    }
+
+    /// Returns the types whose namespace starts with the given segments, e.g. all the types of
+    /// `["pallet_balances"]` and of its submodules.
+    pub fn by_namespace(&self, prefix: &[&str]) -> Vec<TypeMatch<'a>> {
+        let ids = self
+            .by_path
+            .range::<[&str], _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(segments, _)| segments.starts_with(prefix))
+            .filter(|(segments, _)| segments.len() > prefix.len())
+            .flat_map(|(_, ids)| ids);
+        self.matches(ids)
+    }
+
+    fn matches<'b>(&self, ids: impl Iterator<Item = &'b u32>) -> Vec<TypeMatch<'a>> {
+        let mut ids: Vec<u32> = ids.copied().collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .filter_map(|id| {
+                let ty = self.registry.resolve(id)?;
+                let type_params = ty
+                    .type_params
+                    .iter()
+                    .map(|param| {
+                        let id = param.ty.map(|ty| ty.id);
+                        ResolvedTypeParam {
+                            name: param.name.as_ref(),
+                            id,
+                            ty: id.and_then(|id| self.registry.resolve(id)),
+                        }
+                    })
+                    .collect();
+                Some(TypeMatch {
+                    id,
+                    ty,
+                    type_params,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Foo<T> {
+        a: T,
+    }
+
+    mod inner {
+        use crate::*;
+        use scale_info_derive::TypeInfo;
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        pub struct Foo;
+    }
+
+    #[test]
+    fn lookup_types_by_path() {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<(Foo<u8>, Foo<bool>, inner::Foo)>());
+        let registry: PortableRegistry = registry.into();
+        let index = registry.type_index();
+        let namespace = ["scale_info", "lookup", "tests"];
+
+        let foos = index.by_path(&[&namespace[..], &["Foo"]].concat());
+        assert_eq!(foos.len(), 2);
+        for (foo, expected) in foos
+            .iter()
+            .zip([TypeDefPrimitive::U8, TypeDefPrimitive::Bool])
+        {
+            assert_eq!(foo.ty, registry.resolve(foo.id).unwrap());
+            assert_eq!(foo.type_params.len(), 1);
+            assert_eq!(foo.type_params[0].name, "T");
+            assert_eq!(
+                foo.type_params[0].ty.map(|ty| &ty.type_def),
+                Some(&TypeDef::Primitive(expected))
+            );
+        }
+        assert!(foos[0].id < foos[1].id);
+
+        assert_eq!(index.by_ident("Foo").len(), 3);
+        assert_eq!(index.by_namespace(&namespace).len(), 3);
+        assert_eq!(index.by_namespace(&["scale_info", "lookup"]).len(), 3);
+        assert_eq!(
+            index.by_namespace(&[&namespace[..], &["inner"]].concat())[0]
+                .ty
+                .path,
+            Path::from_segments_unchecked(
+                ["scale_info", "lookup", "tests", "inner", "Foo"].map(Into::into)
+            )
+        );
+        assert!(index.by_namespace(&["scale_info", "look"]).is_empty());
+        assert!(index.by_path(&["scale_info", "lookup"]).is_empty());
+        assert!(index.by_ident("Bar").is_empty());
+    }
+}
@@ -26,7 +26,14 @@
 use crate::{
     form::PortableForm,
     interner::Interner,
-    prelude::{collections::BTreeMap, fmt::Debug, mem, vec::Vec},
+    prelude::{
+        collections::BTreeMap,
+        fmt::{self, Debug, Display, Formatter},
+        mem,
+        vec::Vec,
+    },
+    utils::type_refs,
+    validate::{ValidationError, ValidationErrorKind},
     Path, Registry, Type, TypeDef, TypeDefPrimitive,
 };
 use scale::Encode;
@@ -126,72 +133,10 @@ impl PortableRegistry {
             let mut ty = mem::replace(&mut types[id as usize], placeholder_type());
             ty.id = new_id;
 
-            // Now we recursively retain any type parameters in the type we're retaining.
-            // Update their IDs to point to the new locations of the retained types.
-            for param in ty.ty.type_params.iter_mut() {
-                let Some(param_ty) = &param.ty else { continue };
-                let new_id = retain_type(param_ty.id, types, new_types, retained_mappings);
-                param.ty = Some(Into::into(new_id));
-            }
-
-            // Also recurse into any types inside this type to retain them too. Update their IDs
-            // to point to the new locations of the retained types.
-            match &mut ty.ty.type_def {
-                TypeDef::Composite(composite) => {
-                    for field in composite.fields.iter_mut() {
-                        let new_id = retain_type(field.ty.id, types, new_types, retained_mappings);
-                        field.ty = new_id.into();
-                    }
-                }
-                TypeDef::Variant(variant) => {
-                    for var in variant.variants.iter_mut() {
-                        for field in var.fields.iter_mut() {
-                            let new_id =
-                                retain_type(field.ty.id, types, new_types, retained_mappings);
-                            field.ty = new_id.into();
-                        }
-                    }
-                }
-                TypeDef::Sequence(sequence) => {
-                    let new_id =
-                        retain_type(sequence.type_param.id, types, new_types, retained_mappings);
-                    sequence.type_param = new_id.into();
-                }
-                TypeDef::Array(array) => {
-                    let new_id =
-                        retain_type(array.type_param.id, types, new_types, retained_mappings);
-                    array.type_param = new_id.into();
-                }
-                TypeDef::Tuple(tuple) => {
-                    for ty in tuple.fields.iter_mut() {
-                        let new_id = retain_type(ty.id, types, new_types, retained_mappings);
-                        *ty = new_id.into();
-                    }
-                }
-                TypeDef::Primitive(_) => (),
-                TypeDef::Compact(compact) => {
-                    let new_id =
-                        retain_type(compact.type_param.id, types, new_types, retained_mappings);
-                    compact.type_param = new_id.into();
-                }
-                TypeDef::BitSequence(bit_seq) => {
-                    let bit_store_id = retain_type(
-                        bit_seq.bit_store_type.id,
-                        types,
-                        new_types,
-                        retained_mappings,
-                    );
-                    let bit_order_id = retain_type(
-                        bit_seq.bit_order_type.id,
-                        types,
-                        new_types,
-                        retained_mappings,
-                    );
-
-                    bit_seq.bit_store_type = bit_store_id.into();
-                    bit_seq.bit_order_type = bit_order_id.into();
-                }
-            }
+            // Now we recursively retain any types inside this type, including its type
+            // parameters. Update their IDs to point to the new locations of the retained types.
+            ty.ty
+                .map_type_ids(|id| retain_type(id, types, new_types, retained_mappings));
 
             // Now we've updated the IDs etc of this type, we put it into the new registry
             // and override our placeholder type that was saving its space for us.
@@ -211,8 +156,173 @@ impl PortableRegistry {
         self.types = new_types;
         retained_mappings
     }
+
+    /// Merges structurally identical types, i.e. types with the same path, type parameters,
+    /// definition and docs, which only differ by the ids of the types they refer to as long as
+    /// those are themselves identical.
+    ///
+    /// The first of each set of identical types is kept, and all references to the others are
+    /// updated to point to it. Types are then renumbered to remain contiguous, keeping their
+    /// relative order.
+    ///
+    /// Returns the new id of every type, keyed by its old id, or an error for the first
+    /// reference to a type missing from the registry, in which case the registry is left
+    /// unchanged. Such an id could otherwise end up referring to another type once renumbered.
+    pub fn deduplicate(&mut self) -> Result<BTreeMap<u32, u32>, ValidationError> {
+        self.check_type_ids()?;
+
+        // Start by grouping types which are identical apart from the ids they refer to.
+        let mut shapes = BTreeMap::new();
+        let mut classes: Vec<usize> = self
+            .types
+            .iter()
+            .map(|ty| {
+                let mut shape = ty.ty.clone();
+                shape.map_type_ids(|_| 0);
+                let next = shapes.len();
+                *shapes.entry(shape).or_insert(next)
+            })
+            .collect();
+        let mut class_count = shapes.len();
+
+        // Then split groups until all the types of a group refer to types of the same groups,
+        // which handles recursive types without any special casing.
+        let refs: Vec<Vec<u32>> = self.types.iter().map(|ty| type_refs(&ty.ty)).collect();
+        loop {
+            let mut signatures = BTreeMap::new();
+            let refined: Vec<usize> = classes
+                .iter()
+                .zip(&refs)
+                .map(|(class, refs)| {
+                    let ref_classes: Vec<_> = refs
+                        .iter()
+                        .map(|id| classes.get(*id as usize).copied())
+                        .collect();
+                    let next = signatures.len();
+                    *signatures.entry((*class, ref_classes)).or_insert(next)
+                })
+                .collect();
+            classes = refined;
+            if signatures.len() == class_count {
+                break;
+            }
+            class_count = signatures.len();
+        }
+
+        let mut class_ids = crate::prelude::vec![None; class_count];
+        let mut mappings = BTreeMap::new();
+        let mut new_types = Vec::new();
+        for (old_id, ty) in mem::take(&mut self.types).into_iter().enumerate() {
+            let class_id = &mut class_ids[classes[old_id]];
+            let new_id = *class_id.get_or_insert_with(|| {
+                new_types.push(ty);
+                new_types.len() as u32 - 1
+            });
+            mappings.insert(old_id as u32, new_id);
+        }
+        for (new_id, ty) in new_types.iter_mut().enumerate() {
+            ty.id = new_id as u32;
+            ty.ty.map_type_ids(|id| mappings[&id]);
+        }
+
+        self.types = new_types;
+        Ok(mappings)
+    }
+
+    /// Returns an error for the first reference to a type missing from the registry, if any.
+    fn check_type_ids(&self) -> Result<(), ValidationError> {
+        let len = self.types.len();
+        for (position, ty) in self.types.iter().enumerate() {
+            if let Some(id) = type_refs(&ty.ty).into_iter().find(|id| *id as usize >= len) {
+                return Err(ValidationError {
+                    type_id: position as u32,
+                    kind: ValidationErrorKind::DanglingTypeId(id),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Combines several registries into one, merging structurally identical types as
+    /// [`deduplicate`](Self::deduplicate) does, within and across registries.
+    ///
+    /// Returns the merged registry, along with the new id of every type of each of the given
+    /// registries, keyed by its id in that registry. Fails if the ids of a registry don't all
+    /// refer to its own types, see [`validate`](Self::validate), or if the registries hold more
+    /// than `u32::MAX` types in total.
+    pub fn merge<'a, I>(
+        registries: I,
+    ) -> Result<(PortableRegistry, Vec<BTreeMap<u32, u32>>), MergeError>
+    where
+        I: IntoIterator<Item = &'a PortableRegistry>,
+    {
+        let mut merged = PortableRegistry { types: Vec::new() };
+        let mut ranges = Vec::new();
+        for (index, registry) in registries.into_iter().enumerate() {
+            registry
+                .check_type_ids()
+                .map_err(|error| MergeError::DanglingTypeId {
+                    registry: index,
+                    error,
+                })?;
+            let offset = merged.types.len() as u32;
+            let end = u32::try_from(registry.types.len())
+                .ok()
+                .and_then(|len| offset.checked_add(len))
+                .ok_or(MergeError::TooManyTypes)?;
+            ranges.push(offset..end);
+            // Ids are below `end` now that dangling ones have been rejected, so offsetting them
+            // can't overflow.
+            merged
+                .types
+                .extend(registry.types.iter().enumerate().map(|(id, ty)| {
+                    let mut ty = ty.clone();
+                    ty.id = offset + id as u32;
+                    ty.ty.map_type_ids(|id| id + offset);
+                    ty
+                }));
+        }
+
+        let mappings = merged
+            .deduplicate()
+            .expect("dangling type ids are rejected above");
+        let mappings = ranges
+            .into_iter()
+            .map(|range| {
+                let offset = range.start;
+                range.map(|id| (id - offset, mappings[&id])).collect()
+            })
+            .collect();
+        Ok((merged, mappings))
+    }
 }
 
+/// An error returned by [`PortableRegistry::merge`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// A type of one of the registries refers to a type missing from that registry.
+    DanglingTypeId {
+        /// The position of the registry among the merged registries.
+        registry: usize,
+        /// The type referring to the missing type.
+        error: ValidationError,
+    },
+    /// The registries hold more than `u32::MAX` types in total.
+    TooManyTypes,
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DanglingTypeId { registry, error } => write!(f, "registry {registry}: {error}"),
+            Self::TooManyTypes => write!(f, "registries hold more than {} types", u32::MAX),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
 /// Represent a type in it's portable form.
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -308,7 +418,7 @@ mod tests {
 
     use super::*;
     use crate::ty::TypeDefPrimitive;
-    use crate::{build::*, prelude::vec, *};
+    use crate::{build::*, hash::HashOptions, prelude::vec, *};
 
     fn ty<T: TypeInfo + 'static>() -> MetaType {
         MetaType::new::<T>()
@@ -780,4 +890,218 @@ mod tests {
         assert_eq!(Some(&vec_u32_type), registry.resolve(vec_u32_type_id));
         assert_eq!(Some(&composite_type), registry.resolve(composite_type_id));
     }
+
+    fn registry_of(types: impl IntoIterator<Item = Type<PortableForm>>) -> PortableRegistry {
+        PortableRegistry {
+            types: types
+                .into_iter()
+                .enumerate()
+                .map(|(id, ty)| PortableType::new(id as u32, ty))
+                .collect(),
+        }
+    }
+
+    fn named_composite(name: &'static str, field_ty: u32) -> Type<PortableForm> {
+        Type::builder_portable()
+            .path(Path::from_segments_unchecked([name.into()]))
+            .composite(Fields::named().field_portable(|f| f.name("a".into()).ty(field_ty)))
+    }
+
+    #[test]
+    fn deduplicate_merges_identical_types() {
+        let mut registry = registry_of([
+            TypeDefPrimitive::U32.into(),
+            named_composite("Foo", 0),
+            TypeDefPrimitive::U32.into(),
+            named_composite("Foo", 2),
+            TypeDefSequence::new(1.into()).into(),
+            TypeDefSequence::new(3.into()).into(),
+            TypeDefTuple::new_portable([4.into(), 5.into()]).into(),
+            named_composite("Bar", 2),
+        ]);
+
+        let mappings = registry.deduplicate().unwrap();
+
+        assert_eq!(
+            mappings.into_iter().collect::<Vec<_>>(),
+            vec![
+                (0, 0),
+                (1, 1),
+                (2, 0),
+                (3, 1),
+                (4, 2),
+                (5, 2),
+                (6, 3),
+                (7, 4)
+            ]
+        );
+        assert_eq!(
+            registry,
+            registry_of([
+                TypeDefPrimitive::U32.into(),
+                named_composite("Foo", 0),
+                TypeDefSequence::new(1.into()).into(),
+                TypeDefTuple::new_portable([2.into(), 2.into()]).into(),
+                named_composite("Bar", 0),
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_registries() {
+        let first = registry_of([
+            TypeDefPrimitive::U32.into(),
+            named_composite("Foo", 0),
+            TypeDefSequence::new(1.into()).into(),
+        ]);
+        let second = registry_of([
+            TypeDefPrimitive::Bool.into(),
+            named_composite("Foo", 2),
+            TypeDefPrimitive::U32.into(),
+            named_composite("Bar", 0),
+        ]);
+
+        let (merged, mappings) = PortableRegistry::merge([&first, &second]).unwrap();
+
+        assert_eq!(
+            merged,
+            registry_of([
+                TypeDefPrimitive::U32.into(),
+                named_composite("Foo", 0),
+                TypeDefSequence::new(1.into()).into(),
+                TypeDefPrimitive::Bool.into(),
+                named_composite("Bar", 3),
+            ])
+        );
+        assert_eq!(
+            mappings,
+            vec![
+                [(0, 0), (1, 1), (2, 2)].into_iter().collect(),
+                [(0, 3), (1, 1), (2, 0), (3, 4)].into_iter().collect(),
+            ]
+        );
+        for (registry, mappings) in [&first, &second].into_iter().zip(&mappings) {
+            for (old_id, new_id) in mappings {
+                assert_eq!(
+                    merged.type_hash(*new_id, HashOptions::full()),
+                    registry.type_hash(*old_id, HashOptions::full())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn merge_rejects_dangling_type_ids() {
+        // Would refer to the `u32` of the second registry once merged.
+        let first = registry_of([TypeDefSequence::new(1.into()).into()]);
+        let second = registry_of([TypeDefPrimitive::U32.into()]);
+
+        assert_eq!(
+            PortableRegistry::merge([&first, &second]),
+            Err(MergeError::DanglingTypeId {
+                registry: 0,
+                error: ValidationError {
+                    type_id: 0,
+                    kind: ValidationErrorKind::DanglingTypeId(1),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn map_type_ids_visits_every_reference() {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<(
+            Result<Vec<u8>, (bool, [u16; 4])>,
+            Compact<u32>,
+            Option<Option<u64>>,
+        )>());
+        let mut registry: PortableRegistry = registry.into();
+
+        // The type parameters come first, then the fields of `Ok` and `Err`.
+        let result = registry
+            .types
+            .iter()
+            .find(|ty| ty.ty.path.ident().as_deref() == Some("Result"))
+            .unwrap();
+        let params: Vec<u32> = result
+            .ty
+            .type_params
+            .iter()
+            .map(|param| param.ty.unwrap().id)
+            .collect();
+        assert_eq!(type_refs(&result.ty), [params.clone(), params].concat());
+
+        for ty in registry.types.iter_mut() {
+            let expected = type_refs(&ty.ty);
+            let original = ty.ty.clone();
+            let mut visited = Vec::new();
+            ty.ty.map_type_ids(|id| {
+                visited.push(id);
+                id + 100
+            });
+            assert_eq!(visited, expected);
+            assert_eq!(
+                type_refs(&ty.ty),
+                expected.iter().map(|id| id + 100).collect::<Vec<_>>()
+            );
+            ty.ty.map_type_ids(|id| id - 100);
+            assert_eq!(ty.ty, original);
+        }
+    }
+
+    #[test]
+    fn deduplicate_recursive_types() {
+        let option = |some: u32| -> Type<PortableForm> {
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked(["Option".into()]))
+                .variant(Variants::new().variant_unit("None".into(), 0).variant(
+                    "Some".into(),
+                    |v| {
+                        v.index(1)
+                            .fields(Fields::unnamed().field_portable(|f| f.ty(some)))
+                    },
+                ))
+        };
+        let mut registry = registry_of([
+            named_composite("List", 1),
+            option(0),
+            named_composite("List", 3),
+            option(2),
+            // Identical to the first copy, even though it refers back to it.
+            named_composite("List", 5),
+            option(0),
+        ]);
+
+        let mappings = registry.deduplicate().unwrap();
+
+        assert_eq!(
+            mappings.into_iter().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (2, 0), (3, 1), (4, 0), (5, 1)]
+        );
+        assert_eq!(
+            registry,
+            registry_of([named_composite("List", 1), option(0)])
+        );
+    }
+
+    #[test]
+    fn deduplicate_rejects_dangling_type_ids() {
+        let mut registry = registry_of([
+            TypeDefPrimitive::U32.into(),
+            TypeDefPrimitive::U32.into(),
+            TypeDefSequence::new(0.into()).into(),
+            TypeDefSequence::new(4.into()).into(),
+        ]);
+        let before = registry.clone();
+
+        assert_eq!(
+            registry.deduplicate(),
+            Err(ValidationError {
+                type_id: 3,
+                kind: ValidationErrorKind::DanglingTypeId(4),
+            })
+        );
+        assert_eq!(registry, before);
+    }
 }
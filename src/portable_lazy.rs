@@ -0,0 +1,214 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A registry decoding its types on demand.
+//!
+//! Building a [`LazyPortableRegistry`] validates the SCALE encoded bytes of a
+//! [`PortableRegistry`](crate::PortableRegistry) in a single pass, only recording where each
+//! type starts. Types are then decoded when they are resolved.
+
+use crate::prelude::{cmp, vec::Vec};
+
+use crate::{
+    form::PortableFormRef,
+    portable_ref::{decode_compact, decode_primitive, decode_str, decode_type},
+    Type,
+};
+use scale::{Decode, Error};
+
+/// A read-only view of a SCALE encoded [`PortableRegistry`](crate::PortableRegistry), decoding
+/// each type when it is resolved.
+#[derive(Clone, Debug)]
+pub struct LazyPortableRegistry<'a> {
+    bytes: &'a [u8],
+    /// The offset of each type in `bytes`, past its id.
+    offsets: Vec<usize>,
+}
+
+impl<'a> LazyPortableRegistry<'a> {
+    /// Indexes a SCALE encoded [`PortableRegistry`](crate::PortableRegistry) from the start of
+    /// the input, advancing it past the registry.
+    ///
+    /// The input may hold more data after the registry, e.g. the rest of the metadata.
+    pub fn decode(input: &mut &'a [u8]) -> Result<Self, Error> {
+        let bytes = *input;
+        let len = decode_compact(input)? as usize;
+        let mut offsets = Vec::with_capacity(cmp::min(len, input.len()));
+        for _ in 0..len {
+            decode_compact(input)?;
+            offsets.push(bytes.len() - input.len());
+            skip_type(input)?;
+        }
+        Ok(Self {
+            bytes: &bytes[..bytes.len() - input.len()],
+            offsets,
+        })
+    }
+
+    /// Returns the number of types in the registry.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns `true` if the registry contains no types.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Decodes the type with the given identifier, borrowing its strings from the encoded
+    /// registry. Returns `None` if no type found for that ID.
+    pub fn resolve_ref(&self, id: u32) -> Option<Type<PortableFormRef<'a>>> {
+        let offset = *self.offsets.get(id as usize)?;
+        let ty = decode_type(&mut &self.bytes[offset..])
+            .expect("the type was validated when indexing the registry; qed");
+        Some(ty)
+    }
+
+    /// Decodes the type with the given identifier. Returns `None` if no type found for that ID.
+    #[cfg(any(feature = "std", feature = "decode"))]
+    pub fn resolve(&self, id: u32) -> Option<Type<crate::form::PortableForm>> {
+        let offset = *self.offsets.get(id as usize)?;
+        let ty = Type::decode(&mut &self.bytes[offset..])
+            .expect("the type was validated when indexing the registry; qed");
+        Some(ty)
+    }
+}
+
+/// Skips a SCALE encoded [`Type`], validating it without allocating.
+fn skip_type(input: &mut &[u8]) -> Result<(), Error> {
+    // Path segments.
+    skip_vec(input, skip_str)?;
+    // Type parameters.
+    skip_vec(input, |input| {
+        skip_str(input)?;
+        skip_option(input, skip_compact)
+    })?;
+    match u8::decode(input)? {
+        // Composite.
+        0 => skip_fields(input)?,
+        // Variant.
+        1 => skip_vec(input, |input| {
+            skip_str(input)?;
+            skip_fields(input)?;
+            u8::decode(input)?;
+            skip_vec(input, skip_str)
+        })?,
+        // Sequence and compact.
+        2 | 6 => skip_compact(input)?,
+        // Array.
+        3 => {
+            u32::decode(input)?;
+            skip_compact(input)?;
+        }
+        // Tuple.
+        4 => skip_vec(input, skip_compact)?,
+        // Primitive.
+        5 => {
+            decode_primitive(input)?;
+        }
+        // Bit sequence.
+        7 => {
+            skip_compact(input)?;
+            skip_compact(input)?;
+        }
+        _ => return Err("Invalid TypeDef variant index".into()),
+    }
+    // Docs.
+    skip_vec(input, skip_str)
+}
+
+fn skip_fields(input: &mut &[u8]) -> Result<(), Error> {
+    skip_vec(input, |input| {
+        skip_option(input, skip_str)?;
+        skip_compact(input)?;
+        skip_option(input, skip_str)?;
+        skip_vec(input, skip_str)
+    })
+}
+
+fn skip_vec(
+    input: &mut &[u8],
+    mut skip_item: impl FnMut(&mut &[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    for _ in 0..decode_compact(input)? {
+        skip_item(input)?;
+    }
+    Ok(())
+}
+
+fn skip_option(
+    input: &mut &[u8],
+    skip_item: impl FnOnce(&mut &[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    match u8::decode(input)? {
+        0 => Ok(()),
+        1 => skip_item(input),
+        _ => Err("Invalid Option variant index".into()),
+    }
+}
+
+fn skip_str(input: &mut &[u8]) -> Result<(), Error> {
+    decode_str(input).map(|_| ())
+}
+
+fn skip_compact(input: &mut &[u8]) -> Result<(), Error> {
+    decode_compact(input).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use scale::Encode;
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Call {
+        Transfer {
+            to: [u8; 32],
+            #[codec(compact)]
+            amount: u128,
+        },
+        Batch(Vec<Call>, (bool, char)),
+        Remark(Option<String>),
+    }
+
+    #[test]
+    fn resolve_decodes_single_types() {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<Call>());
+        let registry: PortableRegistry = registry.into();
+        let mut encoded = registry.encode();
+        encoded.push(42);
+
+        let input = &mut &encoded[..];
+        let lazy = LazyPortableRegistry::decode(input).unwrap();
+        assert_eq!(*input, [42]);
+        assert_eq!(lazy.len(), registry.types.len());
+
+        for ty in &registry.types {
+            assert_eq!(lazy.resolve(ty.id).as_ref(), Some(&ty.ty));
+            assert_eq!(
+                lazy.resolve_ref(ty.id).unwrap().path.segments,
+                ty.ty.path.segments
+            );
+        }
+        assert_eq!(lazy.resolve(lazy.len() as u32), None);
+
+        for len in 0..encoded.len() - 1 {
+            assert!(LazyPortableRegistry::decode(&mut &encoded[..len]).is_err());
+        }
+    }
+}
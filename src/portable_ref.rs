@@ -0,0 +1,341 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A registry decoded without copying its strings.
+//!
+//! The names, paths and docs of a [`PortableRegistryRef`] borrow from the SCALE encoded bytes
+//! of a [`PortableRegistry`](crate::PortableRegistry), so that decoding large metadata only
+//! allocates the vectors holding them.
+
+use crate::prelude::{cmp, vec::Vec};
+
+use crate::{
+    form::PortableFormRef, Field, Path, Type, TypeDef, TypeDefArray, TypeDefBitSequence,
+    TypeDefCompact, TypeDefComposite, TypeDefPrimitive, TypeDefSequence, TypeDefTuple,
+    TypeDefVariant, TypeParameter, Variant,
+};
+use scale::{Compact, Decode, Error};
+
+/// A read-only registry containing types in their portable form, borrowing their strings from
+/// the bytes they were decoded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortableRegistryRef<'a> {
+    /// The types contained by the [`PortableRegistryRef`].
+    pub types: Vec<PortableTypeRef<'a>>,
+}
+
+/// Represent a type in its portable form, borrowing its strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortableTypeRef<'a> {
+    /// The ID of the portable type.
+    pub id: u32,
+    /// The portable form of the type.
+    pub ty: Type<PortableFormRef<'a>>,
+}
+
+impl<'a> PortableRegistryRef<'a> {
+    /// Decodes a SCALE encoded [`PortableRegistry`](crate::PortableRegistry) from the start of
+    /// the input, advancing it past the registry.
+    ///
+    /// The input may hold more data after the registry, e.g. the rest of the metadata.
+    pub fn decode(input: &mut &'a [u8]) -> Result<Self, Error> {
+        let types = decode_vec(input, |input| {
+            Ok(PortableTypeRef {
+                id: decode_compact(input)?,
+                ty: decode_type(input)?,
+            })
+        })?;
+        Ok(Self { types })
+    }
+
+    /// Returns the type definition for the given identifier, `None` if no type found for that ID.
+    pub fn resolve(&self, id: u32) -> Option<&Type<PortableFormRef<'a>>> {
+        self.types.get(id as usize).map(|ty| &ty.ty)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "decode"))]
+mod owned {
+    use super::*;
+    use crate::{
+        form::PortableForm,
+        prelude::string::{String, ToString},
+        PortableRegistry, PortableType,
+    };
+
+    impl From<PortableRegistryRef<'_>> for PortableRegistry {
+        fn from(registry: PortableRegistryRef<'_>) -> Self {
+            registry.into_owned()
+        }
+    }
+
+    impl PortableRegistryRef<'_> {
+        /// Converts into a registry owning its strings.
+        pub fn into_owned(self) -> PortableRegistry {
+            PortableRegistry {
+                types: self
+                    .types
+                    .iter()
+                    .map(|ty| PortableType::new(ty.id, to_owned_type(&ty.ty)))
+                    .collect(),
+            }
+        }
+    }
+
+    fn to_owned_type(ty: &Type<PortableFormRef>) -> Type<PortableForm> {
+        Type {
+            path: Path {
+                segments: strings(&ty.path.segments),
+            },
+            type_params: ty
+                .type_params
+                .iter()
+                .map(|param| TypeParameter {
+                    name: param.name.to_string(),
+                    ty: param.ty,
+                })
+                .collect(),
+            type_def: match &ty.type_def {
+                TypeDef::Composite(composite) => TypeDef::Composite(TypeDefComposite {
+                    fields: fields(&composite.fields),
+                }),
+                TypeDef::Variant(variant) => TypeDef::Variant(TypeDefVariant {
+                    variants: variant
+                        .variants
+                        .iter()
+                        .map(|variant| Variant {
+                            name: variant.name.to_string(),
+                            fields: fields(&variant.fields),
+                            index: variant.index,
+                            docs: strings(&variant.docs),
+                        })
+                        .collect(),
+                }),
+                TypeDef::Sequence(sequence) => TypeDef::Sequence(TypeDefSequence {
+                    type_param: sequence.type_param,
+                }),
+                TypeDef::Array(array) => TypeDef::Array(TypeDefArray {
+                    len: array.len,
+                    type_param: array.type_param,
+                }),
+                TypeDef::Tuple(tuple) => TypeDef::Tuple(TypeDefTuple {
+                    fields: tuple.fields.clone(),
+                }),
+                TypeDef::Primitive(primitive) => TypeDef::Primitive(primitive.clone()),
+                TypeDef::Compact(compact) => TypeDef::Compact(TypeDefCompact {
+                    type_param: compact.type_param,
+                }),
+                TypeDef::BitSequence(bit_sequence) => TypeDef::BitSequence(TypeDefBitSequence {
+                    bit_store_type: bit_sequence.bit_store_type,
+                    bit_order_type: bit_sequence.bit_order_type,
+                }),
+            },
+            docs: strings(&ty.docs),
+        }
+    }
+
+    fn fields(fields: &[Field<PortableFormRef>]) -> Vec<Field<PortableForm>> {
+        fields
+            .iter()
+            .map(|field| Field {
+                name: field.name.map(ToString::to_string),
+                ty: field.ty,
+                type_name: field.type_name.map(ToString::to_string),
+                docs: strings(&field.docs),
+            })
+            .collect()
+    }
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|s| s.to_string()).collect()
+    }
+}
+
+/// Decodes a SCALE encoded [`Type`], borrowing its strings from the input.
+pub(crate) fn decode_type<'a>(input: &mut &'a [u8]) -> Result<Type<PortableFormRef<'a>>, Error> {
+    let path = Path {
+        segments: decode_vec(input, decode_str)?,
+    };
+    let type_params = decode_vec(input, |input| {
+        Ok(TypeParameter {
+            name: decode_str(input)?,
+            ty: decode_option(input, decode_compact)?.map(Into::into),
+        })
+    })?;
+    let type_def = match u8::decode(input)? {
+        0 => TypeDef::Composite(TypeDefComposite {
+            fields: decode_fields(input)?,
+        }),
+        1 => TypeDef::Variant(TypeDefVariant {
+            variants: decode_vec(input, |input| {
+                Ok(Variant {
+                    name: decode_str(input)?,
+                    fields: decode_fields(input)?,
+                    index: u8::decode(input)?,
+                    docs: decode_vec(input, decode_str)?,
+                })
+            })?,
+        }),
+        2 => TypeDef::Sequence(TypeDefSequence {
+            type_param: decode_compact(input)?.into(),
+        }),
+        3 => TypeDef::Array(TypeDefArray {
+            len: u32::decode(input)?,
+            type_param: decode_compact(input)?.into(),
+        }),
+        4 => TypeDef::Tuple(TypeDefTuple {
+            fields: decode_vec(input, |input| Ok(decode_compact(input)?.into()))?,
+        }),
+        5 => TypeDef::Primitive(decode_primitive(input)?),
+        6 => TypeDef::Compact(TypeDefCompact {
+            type_param: decode_compact(input)?.into(),
+        }),
+        7 => TypeDef::BitSequence(TypeDefBitSequence {
+            bit_store_type: decode_compact(input)?.into(),
+            bit_order_type: decode_compact(input)?.into(),
+        }),
+        _ => return Err("Invalid TypeDef variant index".into()),
+    };
+    let docs = decode_vec(input, decode_str)?;
+    Ok(Type {
+        path,
+        type_params,
+        type_def,
+        docs,
+    })
+}
+
+fn decode_fields<'a>(input: &mut &'a [u8]) -> Result<Vec<Field<PortableFormRef<'a>>>, Error> {
+    decode_vec(input, |input| {
+        Ok(Field {
+            name: decode_option(input, decode_str)?,
+            ty: decode_compact(input)?.into(),
+            type_name: decode_option(input, decode_str)?,
+            docs: decode_vec(input, decode_str)?,
+        })
+    })
+}
+
+pub(crate) fn decode_primitive(input: &mut &[u8]) -> Result<TypeDefPrimitive, Error> {
+    Ok(match u8::decode(input)? {
+        0 => TypeDefPrimitive::Bool,
+        1 => TypeDefPrimitive::Char,
+        2 => TypeDefPrimitive::Str,
+        3 => TypeDefPrimitive::U8,
+        4 => TypeDefPrimitive::U16,
+        5 => TypeDefPrimitive::U32,
+        6 => TypeDefPrimitive::U64,
+        7 => TypeDefPrimitive::U128,
+        8 => TypeDefPrimitive::U256,
+        9 => TypeDefPrimitive::I8,
+        10 => TypeDefPrimitive::I16,
+        11 => TypeDefPrimitive::I32,
+        12 => TypeDefPrimitive::I64,
+        13 => TypeDefPrimitive::I128,
+        14 => TypeDefPrimitive::I256,
+        _ => return Err("Invalid TypeDefPrimitive variant index".into()),
+    })
+}
+
+fn decode_vec<'a, T>(
+    input: &mut &'a [u8],
+    mut decode_item: impl FnMut(&mut &'a [u8]) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    let len = decode_compact(input)? as usize;
+    // Every item takes at least one byte, which bounds the allocation for invalid lengths.
+    let mut items = Vec::with_capacity(cmp::min(len, input.len()));
+    for _ in 0..len {
+        items.push(decode_item(input)?);
+    }
+    Ok(items)
+}
+
+fn decode_option<'a, T>(
+    input: &mut &'a [u8],
+    decode_item: impl FnOnce(&mut &'a [u8]) -> Result<T, Error>,
+) -> Result<Option<T>, Error> {
+    match u8::decode(input)? {
+        0 => Ok(None),
+        1 => decode_item(input).map(Some),
+        _ => Err("Invalid Option variant index".into()),
+    }
+}
+
+pub(crate) fn decode_str<'a>(input: &mut &'a [u8]) -> Result<&'a str, Error> {
+    let len = decode_compact(input)? as usize;
+    if input.len() < len {
+        return Err("Not enough data to decode a string".into());
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    core::str::from_utf8(bytes).map_err(|_| "Invalid UTF-8 string".into())
+}
+
+pub(crate) fn decode_compact(input: &mut &[u8]) -> Result<u32, Error> {
+    Compact::<u32>::decode(input).map(|compact| compact.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale::Encode;
+    use scale_info_derive::TypeInfo;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Call<T> {
+        Transfer {
+            to: [u8; 32],
+            #[codec(compact)]
+            amount: T,
+        },
+        Batch(Vec<Call<T>>, (bool, char)),
+    }
+
+    fn registry() -> PortableRegistry {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<(Call<u128>, Option<String>)>());
+        let mut registry: PortableRegistry = registry.into();
+        registry.types[1].ty.docs = vec!["A call.".into()];
+        registry
+    }
+
+    #[test]
+    fn decode_borrows_strings() {
+        let registry = registry();
+        let mut encoded = registry.encode();
+        encoded.extend([1, 2, 3]);
+
+        let input = &mut &encoded[..];
+        let borrowed = PortableRegistryRef::decode(input).unwrap();
+        assert_eq!(*input, [1, 2, 3]);
+        let ident = borrowed.resolve(1).unwrap().path.ident().unwrap();
+        assert_eq!(ident, "Call");
+        assert!(encoded.as_ptr_range().contains(&ident.as_ptr()));
+        assert_eq!(PortableRegistry::from(borrowed), registry);
+    }
+
+    #[test]
+    fn decode_invalid_input() {
+        let encoded = registry().encode();
+        for len in 0..encoded.len() {
+            assert!(PortableRegistryRef::decode(&mut &encoded[..len]).is_err());
+        }
+
+        // A single type whose path is a single invalid UTF-8 segment.
+        let invalid = [4, 0, 4, 4, 0xff, 0xfe, 0xfd, 0xfc];
+        assert!(PortableRegistryRef::decode(&mut &invalid[..]).is_err());
+    }
+}
@@ -0,0 +1,302 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering of registry types as Rust-like signatures, e.g.
+//! `Option<Vec<(u32, my_crate::Foo<bool>)>>`.
+//!
+//! Types with a [`Path`](crate::Path) are rendered by their path followed by their type
+//! parameters, and other types are resolved inline. Type arguments nested deeper than the
+//! maximum depth are rendered as `_`. A type referring to itself without going through a path,
+//! which can't be rendered in full, is rendered as `_` where it recurses.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{MetaType, PortableRegistry, Registry};
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<Option<Vec<(u32, bool)>>>()).id;
+//! let registry: PortableRegistry = registry.into();
+//!
+//! assert_eq!(registry.display_type(id).to_string(), "Option<Vec<(u32, bool)>>");
+//! assert_eq!(registry.display_type(id).max_depth(1).to_string(), "Option<Vec<_>>");
+//! ```
+
+use crate::prelude::{
+    fmt::{self, Display, Formatter},
+    vec::Vec,
+};
+
+use crate::{form::PortableForm, utils::primitive_kind, Field, PortableRegistry, TypeDef};
+
+impl PortableRegistry {
+    /// Returns a [`Display`] implementation rendering the type with the given id as a Rust-like
+    /// signature.
+    pub fn display_type(&self, id: u32) -> TypeDisplay<'_> {
+        TypeDisplay {
+            registry: self,
+            id,
+            max_depth: None,
+            full_paths: true,
+        }
+    }
+}
+
+/// Renders a type of a [`PortableRegistry`] as a Rust-like signature.
+///
+/// Created by [`PortableRegistry::display_type`].
+#[derive(Clone, Copy, Debug)]
+pub struct TypeDisplay<'a> {
+    registry: &'a PortableRegistry,
+    id: u32,
+    max_depth: Option<usize>,
+    full_paths: bool,
+}
+
+impl TypeDisplay<'_> {
+    /// Sets the maximum nesting depth of the type arguments to render, beyond which they are
+    /// rendered as `_`. With a depth of `0`, only the outermost type is rendered.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets whether types are rendered with their full path, e.g. `my_crate::Foo`, which is the
+    /// default, or with their identifier only, e.g. `Foo`.
+    pub fn full_paths(mut self, full_paths: bool) -> Self {
+        self.full_paths = full_paths;
+        self
+    }
+
+    fn write_type(
+        &self,
+        f: &mut Formatter<'_>,
+        id: u32,
+        depth: usize,
+        stack: &mut Vec<u32>,
+    ) -> fmt::Result {
+        if self.max_depth.map_or(false, |max| depth > max) {
+            return write!(f, "_");
+        }
+        let ty = match self.registry.resolve(id) {
+            Some(ty) => ty,
+            None => return write!(f, "<unknown type {id}>"),
+        };
+        if stack.contains(&id) {
+            return match ty.path.ident() {
+                Some(ident) if !self.full_paths => write!(f, "{ident}"),
+                Some(_) => write!(f, "{}", ty.path),
+                None => write!(f, "_"),
+            };
+        }
+
+        stack.push(id);
+        let result = self.write_type_def(f, id, depth, stack);
+        stack.pop();
+        result
+    }
+
+    fn write_type_def(
+        &self,
+        f: &mut Formatter<'_>,
+        id: u32,
+        depth: usize,
+        stack: &mut Vec<u32>,
+    ) -> fmt::Result {
+        let ty = self
+            .registry
+            .resolve(id)
+            .expect("the type was resolved by the caller; qed");
+
+        if let Some(ident) = ty.path.ident() {
+            if self.full_paths {
+                write!(f, "{}", ty.path)?;
+            } else {
+                write!(f, "{ident}")?;
+            }
+            if !ty.type_params.is_empty() {
+                write!(f, "<")?;
+                for (i, param) in ty.type_params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match param.ty {
+                        Some(param) => self.write_type(f, param.id, depth + 1, stack)?,
+                        None => write!(f, "{}", param.name)?,
+                    }
+                }
+                write!(f, ">")?;
+            }
+            return Ok(());
+        }
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) => self.write_fields(f, &composite.fields, depth, stack),
+            TypeDef::Variant(variant) => {
+                write!(f, "enum {{ ")?;
+                for (i, variant) in variant.variants.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", variant.name)?;
+                    if !variant.fields.is_empty() {
+                        self.write_fields(f, &variant.fields, depth, stack)?;
+                    }
+                }
+                write!(f, " }}")
+            }
+            TypeDef::Sequence(sequence) => {
+                write!(f, "Vec<")?;
+                self.write_type(f, sequence.type_param.id, depth + 1, stack)?;
+                write!(f, ">")
+            }
+            TypeDef::Array(array) => {
+                write!(f, "[")?;
+                self.write_type(f, array.type_param.id, depth + 1, stack)?;
+                write!(f, "; {}]", array.len)
+            }
+            TypeDef::Tuple(tuple) => {
+                write!(f, "(")?;
+                for (i, field) in tuple.fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    self.write_type(f, field.id, depth + 1, stack)?;
+                }
+                if tuple.fields.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            TypeDef::Primitive(primitive) => write!(f, "{}", primitive_kind(primitive)),
+            TypeDef::Compact(compact) => {
+                write!(f, "Compact<")?;
+                self.write_type(f, compact.type_param.id, depth + 1, stack)?;
+                write!(f, ">")
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                write!(f, "BitVec<")?;
+                self.write_type(f, bit_sequence.bit_store_type.id, depth + 1, stack)?;
+                write!(f, ", ")?;
+                self.write_type(f, bit_sequence.bit_order_type.id, depth + 1, stack)?;
+                write!(f, ">")
+            }
+        }
+    }
+
+    /// Writes the fields of a type or variant without a path, e.g. `{ a: u8 }` or `(u8)`.
+    fn write_fields(
+        &self,
+        f: &mut Formatter<'_>,
+        fields: &[Field<PortableForm>],
+        depth: usize,
+        stack: &mut Vec<u32>,
+    ) -> fmt::Result {
+        let named = !fields.is_empty() && fields.iter().all(|f| f.name.is_some());
+        write!(f, "{}", if named { "{ " } else { "(" })?;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if let (true, Some(name)) = (named, &field.name) {
+                write!(f, "{name}: ")?;
+            }
+            self.write_type(f, field.ty.id, depth + 1, stack)?;
+        }
+        write!(f, "{}", if named { " }" } else { ")" })
+    }
+}
+
+impl Display for TypeDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_type(f, self.id, 0, &mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use scale_info_derive::TypeInfo;
+
+    fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        (id, registry.into())
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Foo<T> {
+        a: T,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Node {
+        children: Vec<Node>,
+        #[codec(compact)]
+        weight: u64,
+    }
+
+    #[test]
+    fn display_nested_types() {
+        let (id, registry) = registry_with::<Option<Vec<(u32, Foo<bool>)>>>();
+        let foo = format!("{}::Foo", module_path!());
+        assert_eq!(
+            registry.display_type(id).to_string(),
+            format!("Option<Vec<(u32, {foo}<bool>)>>")
+        );
+        assert_eq!(
+            registry.display_type(id).full_paths(false).to_string(),
+            "Option<Vec<(u32, Foo<bool>)>>"
+        );
+        assert_eq!(
+            registry.display_type(id).max_depth(2).to_string(),
+            "Option<Vec<(_, _)>>"
+        );
+        assert_eq!(
+            registry.display_type(id).max_depth(0).to_string(),
+            "Option<_>"
+        );
+
+        let (id, registry) = registry_with::<([u8; 4], (char,), Result<(), String>)>();
+        assert_eq!(
+            registry.display_type(id).to_string(),
+            "([u8; 4], (char,), Result<(), str>)"
+        );
+    }
+
+    #[test]
+    fn display_recursive_types() {
+        let (id, registry) = registry_with::<Vec<Node>>();
+        let node = format!("{}::Node", module_path!());
+        assert_eq!(
+            registry.display_type(id).to_string(),
+            format!("Vec<{node}>")
+        );
+
+        // Without a path, the fields of the type are rendered inline, up to where it recurses.
+        let node_id = match &registry.resolve(id).unwrap().type_def {
+            TypeDef::Sequence(sequence) => sequence.type_param.id,
+            _ => unreachable!(),
+        };
+        let mut registry = registry;
+        registry.types[node_id as usize].ty.path = Path::default();
+        assert_eq!(
+            registry.display_type(id).to_string(),
+            "Vec<{ children: _, weight: Compact<u64> }>"
+        );
+        assert_eq!(registry.display_type(42).to_string(), "<unknown type 42>");
+    }
+}
@@ -0,0 +1,511 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounds on the size of the SCALE encoding of the values of registry types.
+//!
+//! Sequences, strings and bit sequences are prefixed by their compact encoded length, so they
+//! take at least one byte and have no maximum size. Compact integers take between one byte
+//! and one more byte than their integer type, e.g. up to 17 bytes for a `Compact<u128>`, and
+//! enums take one byte for their index followed by the fields of the variant.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{size::SizeBounds, MetaType, PortableRegistry, Registry};
+//! let mut registry = Registry::new();
+//! let fixed = registry.register_type(&MetaType::new::<([u8; 32], u64)>()).id;
+//! let option = registry.register_type(&MetaType::new::<Option<u128>>()).id;
+//! let registry: PortableRegistry = registry.into();
+//!
+//! assert_eq!(registry.fixed_encoded_size(fixed), Some(40));
+//! assert_eq!(registry.fixed_encoded_size(option), None);
+//! assert_eq!(
+//!     registry.encoded_size_bounds(option),
+//!     Some(SizeBounds { min: 1, max: Some(17) })
+//! );
+//! ```
+
+use crate::prelude::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    graph::ComponentIndices,
+    utils::{primitive_size, type_refs},
+    PortableRegistry, TypeDef, TypeDefPrimitive,
+};
+
+/// The minimum and maximum size of the SCALE encoding of the values of a type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeBounds {
+    /// The minimum encoded size, in bytes.
+    pub min: usize,
+    /// The maximum encoded size, in bytes, or `None` if the encoding has no maximum size,
+    /// e.g. for sequences.
+    pub max: Option<usize>,
+}
+
+impl SizeBounds {
+    /// Returns the encoded size of all the values of the type, if it is the same for all of
+    /// them.
+    pub fn fixed(&self) -> Option<usize> {
+        if self.max == Some(self.min) {
+            Some(self.min)
+        } else {
+            None
+        }
+    }
+}
+
+impl PortableRegistry {
+    /// Returns the bounds on the encoded size of the values of the type with the given id.
+    ///
+    /// Returns `None` if the type, or any type it refers to, could not be found, if it contains
+    /// a compact encoding of a type which is not an unsigned integer, or if the type has no
+    /// values of finite size, e.g. an enum without variants.
+    ///
+    /// Use a [`SizeEstimator`] to estimate the size of many types of the same registry.
+    pub fn encoded_size_bounds(&self, id: u32) -> Option<SizeBounds> {
+        SizeEstimator::new(self).bounds(id)
+    }
+
+    /// Returns the encoded size of the values of the type with the given id, if it is the same
+    /// for all of them, as for primitives other than `str`, arrays, and tuples and structs of
+    /// fixed size fields.
+    pub fn fixed_encoded_size(&self, id: u32) -> Option<usize> {
+        self.encoded_size_bounds(id)?.fixed()
+    }
+}
+
+/// Computes bounds on the encoded size of the types of a registry, caching the bounds of every
+/// type visited along the way.
+#[derive(Debug)]
+pub struct SizeEstimator<'a> {
+    registry: &'a PortableRegistry,
+    /// The groups of mutually recursive types, computed for the types reachable from the
+    /// estimated types only.
+    components: ComponentIndices,
+    /// The bounds of every type visited, `None` for the types whose bounds can't be computed.
+    cache: BTreeMap<u32, Option<Bounds>>,
+}
+
+/// The bounds of a type while walking the registry.
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    /// `None` if no value of the type has a finite size, i.e. the type is uninhabited or its
+    /// values always refer back to an enclosing type.
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+impl Bounds {
+    fn fixed(size: usize) -> Self {
+        Self {
+            min: Some(size),
+            max: Some(size),
+        }
+    }
+
+    fn at_least(size: usize) -> Self {
+        Self {
+            min: Some(size),
+            max: None,
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            min: self.min.zip(other.min).map(|(a, b)| a.saturating_add(b)),
+            max: self.max.zip(other.max).and_then(|(a, b)| a.checked_add(b)),
+        }
+    }
+}
+
+impl<'a> SizeEstimator<'a> {
+    /// Creates an estimator for the types of the given registry.
+    pub fn new(registry: &'a PortableRegistry) -> Self {
+        Self {
+            registry,
+            components: ComponentIndices::new(),
+            cache: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the bounds on the encoded size of the values of the type with the given id, see
+    /// [`PortableRegistry::encoded_size_bounds`].
+    pub fn bounds(&mut self, id: u32) -> Option<SizeBounds> {
+        let bounds = self.type_bounds(id)?;
+        Some(SizeBounds {
+            min: bounds.min?,
+            max: bounds.max,
+        })
+    }
+
+    fn type_bounds(&mut self, id: u32) -> Option<Bounds> {
+        if let Some(bounds) = self.cache.get(&id) {
+            return *bounds;
+        }
+        let registry = self.registry;
+        registry.resolve(id)?;
+        let references = |node: usize| {
+            let mut refs = type_refs(&registry.types[node].ty);
+            refs.retain(|id| (*id as usize) < registry.types.len());
+            refs
+        };
+        // Groups are numbered after the groups they refer to, so bounding the groups found along
+        // the way in order means the types referred to outside of a group are already cached.
+        let first = self.components.len();
+        let component = self.components.get(id as usize, &references);
+        for component in first..=component {
+            self.group_bounds(component);
+        }
+        self.cache.get(&id).copied().flatten()
+    }
+
+    /// Computes and caches the bounds of every type of a group of mutually recursive types.
+    fn group_bounds(&mut self, component: usize) {
+        let members: Vec<u32> = self
+            .components
+            .members(component)
+            .iter()
+            .map(|member| *member as u32)
+            .collect();
+        if let [id] = members[..] {
+            if !type_refs(&self.registry.types[id as usize].ty).contains(&id) {
+                let bounds = self.type_def_bounds(id);
+                self.cache.insert(id, bounds);
+                return;
+            }
+        }
+
+        // Values of mutually recursive types can nest without end, so they have no maximum
+        // size. Their minimum sizes only ever decrease, from unknown to the size of the smallest
+        // value found so far, so this reaches a fixed point.
+        let unknown = Bounds {
+            min: None,
+            max: None,
+        };
+        for member in &members {
+            self.cache.insert(*member, Some(unknown));
+        }
+        loop {
+            let mut changed = false;
+            for member in &members {
+                let min = match self.type_def_bounds(*member) {
+                    Some(bounds) => bounds.min,
+                    None => {
+                        // Every type of the group refers to the failing one, directly or not.
+                        for member in &members {
+                            self.cache.insert(*member, None);
+                        }
+                        return;
+                    }
+                };
+                if self.cache[member].map(|cached| cached.min) != Some(min) {
+                    self.cache.insert(*member, Some(Bounds { min, max: None }));
+                    changed = true;
+                }
+            }
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    fn type_def_bounds(&mut self, id: u32) -> Option<Bounds> {
+        let registry = self.registry;
+        let ty = registry.resolve(id)?;
+        let bounds = match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                self.sum(composite.fields.iter().map(|field| field.ty.id))?
+            }
+            TypeDef::Variant(variant) => {
+                let mut min: Option<usize> = None;
+                let mut max = Some(0);
+                for variant in &variant.variants {
+                    let bounds = self.sum(variant.fields.iter().map(|field| field.ty.id))?;
+                    min = match (min, bounds.min) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                    max = max.zip(bounds.max).map(|(a, b)| a.max(b));
+                }
+                if variant.variants.is_empty() {
+                    // An enum without variants has no values, so it doesn't add to the size of
+                    // the enclosing types.
+                    Bounds {
+                        min: None,
+                        max: Some(0),
+                    }
+                } else {
+                    Bounds {
+                        min: min.map(|min| min.saturating_add(1)),
+                        max: max.and_then(|max| max.checked_add(1)),
+                    }
+                }
+            }
+            TypeDef::Sequence(_) => Bounds::at_least(1),
+            TypeDef::Array(array) => {
+                if array.len == 0 {
+                    Bounds::fixed(0)
+                } else {
+                    let item = self.type_bounds(array.type_param.id)?;
+                    let len = array.len as usize;
+                    Bounds {
+                        min: item.min.map(|min| min.saturating_mul(len)),
+                        max: item.max.and_then(|max| max.checked_mul(len)),
+                    }
+                }
+            }
+            TypeDef::Tuple(tuple) => self.sum(tuple.fields.iter().map(|field| field.id))?,
+            TypeDef::Primitive(primitive) => match primitive_size(primitive) {
+                Some(size) => Bounds::fixed(size),
+                None => Bounds::at_least(1),
+            },
+            TypeDef::Compact(compact) => self.compact_bounds(compact.type_param.id)?,
+            TypeDef::BitSequence(_) => Bounds::at_least(1),
+        };
+        Some(bounds)
+    }
+
+    fn sum(&mut self, ids: impl Iterator<Item = u32>) -> Option<Bounds> {
+        let mut bounds = Bounds::fixed(0);
+        for id in ids {
+            bounds = bounds.add(self.type_bounds(id)?);
+        }
+        Some(bounds)
+    }
+
+    /// Returns the bounds of the compact encoding of the given type, which is either an unsigned
+    /// integer or a wrapper of a single compactable field.
+    fn compact_bounds(&self, mut id: u32) -> Option<Bounds> {
+        // A type wrapping itself has no integer to compact, so give up once more wrappers have
+        // been unwrapped than there are types.
+        for _ in 0..=self.registry.types.len() {
+            let ty = self.registry.resolve(id)?;
+            let field = match &ty.type_def {
+                TypeDef::Primitive(primitive) => {
+                    let max = match primitive {
+                        TypeDefPrimitive::U8 => 2,
+                        TypeDefPrimitive::U16 => 4,
+                        TypeDefPrimitive::U32 => 5,
+                        TypeDefPrimitive::U64 => 9,
+                        TypeDefPrimitive::U128 => 17,
+                        _ => return None,
+                    };
+                    return Some(Bounds {
+                        min: Some(1),
+                        max: Some(max),
+                    });
+                }
+                TypeDef::Composite(composite) => match &composite.fields[..] {
+                    [] => None,
+                    [field] => Some(field.ty.id),
+                    _ => return None,
+                },
+                TypeDef::Tuple(tuple) => match &tuple.fields[..] {
+                    [] => None,
+                    [field] => Some(field.id),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            match field {
+                Some(field) => id = field,
+                // Nothing is encoded for a compact unit type.
+                None => return Some(Bounds::fixed(0)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        build::{Fields, Variants},
+        form::PortableForm,
+        prelude::vec,
+        *,
+    };
+    use scale::{Compact, Encode};
+    use scale_info_derive::TypeInfo;
+
+    fn bounds_of<T: TypeInfo + 'static>() -> Option<SizeBounds> {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        PortableRegistry::from(registry).encoded_size_bounds(id)
+    }
+
+    fn bounds(min: usize, max: Option<usize>) -> Option<SizeBounds> {
+        Some(SizeBounds { min, max })
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo, Encode)]
+    struct Fixed {
+        a: [u16; 3],
+        b: (bool, u32),
+        c: (),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Call {
+        Transfer {
+            to: [u8; 32],
+            #[codec(compact)]
+            amount: u128,
+        },
+        Remark(Vec<u8>),
+        Noop,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Expr {
+        Value(u32),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Never {}
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Wrapper(#[codec(compact)] u64);
+
+    #[test]
+    fn fixed_size_types() {
+        let fixed = Fixed {
+            a: [0; 3],
+            b: (false, 0),
+            c: (),
+        };
+        assert_eq!(bounds_of::<Fixed>(), bounds(fixed.encoded_size(), Some(11)));
+        assert_eq!(bounds_of::<char>(), bounds(4, Some(4)));
+        assert_eq!(bounds_of::<[u8; 0]>(), bounds(0, Some(0)));
+        assert_eq!(bounds_of::<[Vec<u8>; 2]>(), bounds(2, None));
+
+        let mut registry = Registry::new();
+        let ids = [
+            registry.register_type(&MetaType::new::<u64>()).id,
+            registry.register_type(&MetaType::new::<String>()).id,
+        ];
+        let mut registry: PortableRegistry = registry.into();
+        registry.types[ids[0] as usize].ty.type_def = TypeDefPrimitive::U256.into();
+        registry.types[ids[1] as usize].ty.type_def = TypeDefPrimitive::I256.into();
+        assert_eq!(registry.fixed_encoded_size(ids[0]), Some(32));
+        assert_eq!(registry.fixed_encoded_size(ids[1]), Some(32));
+        assert_eq!(registry.fixed_encoded_size(1000), None);
+    }
+
+    #[test]
+    fn variable_size_types() {
+        assert_eq!(bounds_of::<Option<u32>>(), bounds(1, Some(5)));
+        assert_eq!(bounds_of::<String>(), bounds(1, None));
+        assert_eq!(bounds_of::<Call>(), bounds(1, None));
+        assert_eq!(bounds_of::<Expr>(), bounds(5, None));
+        assert_eq!(bounds_of::<Option<Never>>(), bounds(1, Some(1)));
+        assert_eq!(bounds_of::<Never>(), None);
+
+        for (bounds, max) in [
+            (bounds_of::<Compact<u8>>(), u8::MAX.into()),
+            (bounds_of::<Compact<u16>>(), u16::MAX.into()),
+            (bounds_of::<Compact<u32>>(), u32::MAX.into()),
+            (bounds_of::<Compact<u64>>(), u64::MAX.into()),
+            (bounds_of::<Compact<u128>>(), u128::MAX),
+        ] {
+            assert_eq!(bounds, self::bounds(1, Some(Compact(max).encoded_size())));
+        }
+        assert_eq!(
+            bounds_of::<Wrapper>(),
+            bounds(1, Some(Compact(u64::MAX).encoded_size()))
+        );
+        assert_eq!(bounds_of::<Compact<()>>(), bounds(0, Some(0)));
+    }
+
+    #[test]
+    fn dense_recursive_group() {
+        // `u8`, then a cycle of pairs each holding two of the next, closed by an enum whose
+        // `Leaf` variant holds a `u8` and whose `Node` variant holds two of the first pair.
+        let len = 24;
+        let mut types: Vec<Type<PortableForm>> = vec![TypeDefPrimitive::U8.into()];
+        for next in 2..=len {
+            types.push(TypeDefTuple::new_portable(vec![next.into(), next.into()]).into());
+        }
+        types.push(
+            Type::builder_portable()
+                .path(Path::from_segments_unchecked(["Tree".into()]))
+                .variant(
+                    Variants::new()
+                        .variant("Leaf".into(), |v| {
+                            v.index(0)
+                                .fields(Fields::unnamed().field_portable(|f| f.ty(0)))
+                        })
+                        .variant("Node".into(), |v| {
+                            v.index(1).fields(
+                                Fields::unnamed()
+                                    .field_portable(|f| f.ty(1))
+                                    .field_portable(|f| f.ty(1)),
+                            )
+                        }),
+                ),
+        );
+        let registry = PortableRegistry {
+            types: types
+                .into_iter()
+                .enumerate()
+                .map(|(id, ty)| PortableType::new(id as u32, ty))
+                .collect(),
+        };
+
+        let mut estimator = SizeEstimator::new(&registry);
+        assert_eq!(estimator.bounds(len), bounds(2, None));
+        assert_eq!(estimator.bounds(1), bounds(1 << len, None));
+        assert_eq!(registry.encoded_size_bounds(1), bounds(1 << len, None));
+    }
+
+    #[test]
+    fn deep_chains() {
+        // Types each wrapping the next one, the last one being a `u64`.
+        let chain = |len: u32, wrap: fn(u32) -> Type<PortableForm>| {
+            let mut types: Vec<_> = (0..len)
+                .map(|id| PortableType::new(id, wrap(id + 1)))
+                .collect();
+            types.push(PortableType::new(len, TypeDefPrimitive::U64.into()));
+            PortableRegistry { types }
+        };
+        // Run on a thread with a small stack, as walking the chains recursively would overflow it.
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || {
+                let len = 100_000;
+                let arrays = chain(len, |next| TypeDefArray::new(1, next.into()).into());
+                assert_eq!(arrays.fixed_encoded_size(0), Some(8));
+
+                let mut compact = chain(len, |next| {
+                    TypeDefTuple::new_portable(vec![next.into()]).into()
+                });
+                compact.types.push(PortableType::new(
+                    len + 1,
+                    TypeDefCompact::new(0.into()).into(),
+                ));
+                assert_eq!(compact.encoded_size_bounds(len + 1), bounds(1, Some(9)));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Removal of the parts of a [`PortableRegistry`] which don't affect the encoding of its
+//! types, to shrink its encoded size.
+//!
+//! Whether docs are captured is decided when types are registered, see the `docs` feature.
+//! Stripping a registry afterwards allows producing both a full and a stripped registry from
+//! the same build.
+//!
+//! # Example
+//!
+//! ```
+//! # use scale_info::{strip::StripOptions, MetaType, PortableRegistry, Registry};
+//! let mut registry = Registry::new();
+//! let id = registry.register_type(&MetaType::new::<Option<bool>>()).id;
+//! let mut registry: PortableRegistry = registry.into();
+//!
+//! registry.strip(&StripOptions::all());
+//! assert!(registry.resolve(id).unwrap().type_params[0].name.is_empty());
+//! ```
+
+use crate::prelude::vec::Vec;
+
+use crate::{form::PortableForm, Field, Path, PortableRegistry, Type, TypeDef};
+
+/// Selects which parts of the types of a registry to remove.
+///
+/// The default options remove nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StripOptions {
+    /// Remove the docs of types, fields and variants.
+    pub docs: bool,
+    /// Remove the type names of fields.
+    pub type_names: bool,
+    /// Replace the names of type parameters with empty strings.
+    pub type_param_names: bool,
+    /// Leave the types whose path starts with one of these paths untouched.
+    pub keep_paths: Vec<Path<PortableForm>>,
+}
+
+impl StripOptions {
+    /// Remove docs, type names and type parameter names from all types.
+    pub fn all() -> Self {
+        Self {
+            docs: true,
+            type_names: true,
+            type_param_names: true,
+            keep_paths: Vec::new(),
+        }
+    }
+
+    /// Leaves the types whose path starts with the given path untouched, e.g. all the types of
+    /// a module.
+    pub fn keep_path(mut self, path: Path<PortableForm>) -> Self {
+        self.keep_paths.push(path);
+        self
+    }
+
+    fn keeps(&self, path: &Path<PortableForm>) -> bool {
+        self.keep_paths
+            .iter()
+            .any(|kept| path.segments.starts_with(&kept.segments))
+    }
+}
+
+impl PortableRegistry {
+    /// Removes the docs, field type names or type parameter names selected by the options from
+    /// the types of the registry. Type ids are left unchanged.
+    pub fn strip(&mut self, options: &StripOptions) {
+        for ty in self.types.iter_mut() {
+            if !options.keeps(&ty.ty.path) {
+                strip_type(&mut ty.ty, options);
+            }
+        }
+    }
+}
+
+fn strip_type(ty: &mut Type<PortableForm>, options: &StripOptions) {
+    if options.docs {
+        ty.docs.clear();
+    }
+    if options.type_param_names {
+        for param in ty.type_params.iter_mut() {
+            param.name = Default::default();
+        }
+    }
+    match &mut ty.type_def {
+        TypeDef::Composite(composite) => strip_fields(&mut composite.fields, options),
+        TypeDef::Variant(variant) => {
+            for variant in variant.variants.iter_mut() {
+                if options.docs {
+                    variant.docs.clear();
+                }
+                strip_fields(&mut variant.fields, options);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn strip_fields(fields: &mut [Field<PortableForm>], options: &StripOptions) {
+    for field in fields {
+        if options.docs {
+            field.docs.clear();
+        }
+        if options.type_names {
+            field.type_name = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build::*, prelude::vec, *};
+
+    fn named_type(segments: &[&'static str]) -> Type<PortableForm> {
+        let mut ty = Type::builder_portable()
+            .path(Path::from_segments_unchecked(
+                segments.iter().map(|segment| (*segment).into()),
+            ))
+            .type_params([TypeParameter::new_portable("T".into(), Some(0.into()))])
+            .variant(Variants::new().variant("A".into(), |v| {
+                v.index(0).fields(
+                    Fields::named()
+                        .field_portable(|f| f.name("a".into()).ty(0).type_name("T".into())),
+                )
+            }));
+        ty.docs = vec!["A type.".into()];
+        if let TypeDef::Variant(variant) = &mut ty.type_def {
+            variant.variants[0].docs = vec!["A variant.".into()];
+            variant.variants[0].fields[0].docs = vec!["A field.".into()];
+        }
+        ty
+    }
+
+    fn registry() -> PortableRegistry {
+        PortableRegistry {
+            types: vec![
+                PortableType::new(
+                    0,
+                    Type::new(Path::default(), vec![], TypeDefPrimitive::U8, vec![]),
+                ),
+                PortableType::new(1, named_type(&["app", "Kept"])),
+                PortableType::new(2, named_type(&["other", "Stripped"])),
+            ],
+        }
+    }
+
+    #[test]
+    fn strip_keeps_selected_paths() {
+        let mut registry = registry();
+        let options = StripOptions::all().keep_path(Path::from_segments_unchecked(["app".into()]));
+        registry.strip(&options);
+
+        assert_eq!(registry.types[..2], self::registry().types[..2]);
+        let stripped = &registry.types[2].ty;
+        assert!(stripped.docs.is_empty());
+        assert_eq!(stripped.type_params[0].name, "");
+        let variant = match &stripped.type_def {
+            TypeDef::Variant(variant) => &variant.variants[0],
+            _ => unreachable!(),
+        };
+        assert!(variant.docs.is_empty());
+        assert_eq!(variant.fields[0].name, Some("a".into()));
+        assert_eq!(variant.fields[0].type_name, None);
+        assert!(variant.fields[0].docs.is_empty());
+    }
+
+    #[test]
+    fn strip_selected_parts() {
+        let mut registry = registry();
+        registry.strip(&StripOptions {
+            docs: true,
+            ..Default::default()
+        });
+        let ty = &registry.types[2].ty;
+        assert!(ty.docs.is_empty());
+        assert_eq!(ty.type_params[0].name, "T");
+
+        let mut registry = self::registry();
+        registry.strip(&StripOptions::default());
+        assert_eq!(registry, self::registry());
+    }
+}
@@ -101,6 +101,24 @@ impl IntoPortable for Field {
     }
 }
 
+impl Field<PortableForm> {
+    /// Replaces the type id of the field with the id returned by `f`.
+    pub fn map_type_ids<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        self.ty = f(self.ty.id).into();
+    }
+
+    /// Calls `f` with the type id of the field.
+    pub fn visit_type_ids<F>(&self, mut f: F)
+    where
+        F: FnMut(u32),
+    {
+        f(self.ty.id);
+    }
+}
+
 impl<T> Field<T>
 where
     T: Form,
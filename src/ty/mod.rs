@@ -114,6 +114,53 @@ impl Type {
     }
 }
 
+impl Type<PortableForm> {
+    /// Replaces every type id referred to by the type with the id returned by `f`, starting
+    /// with the ids of its type parameters and then those of its definition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use scale_info::{form::PortableForm, Path, Type, TypeDefSequence};
+    /// let mut ty: Type<PortableForm> =
+    ///     Type::new(Path::default(), vec![], TypeDefSequence::new(1.into()), vec![]);
+    /// ty.map_type_ids(|id| id + 10);
+    /// assert_eq!(ty.type_def, TypeDefSequence::new(11.into()).into());
+    /// ```
+    pub fn map_type_ids<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        for param in self.type_params.iter_mut() {
+            param.map_type_ids(&mut f);
+        }
+        self.type_def.map_type_ids(f);
+    }
+
+    /// Calls `f` with every type id referred to by the type, in the order
+    /// [`map_type_ids`](Self::map_type_ids) replaces them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use scale_info::{form::PortableForm, Path, Type, TypeDefTuple};
+    /// let tuple = TypeDefTuple::new_portable(vec![1.into(), 2.into()]);
+    /// let ty: Type<PortableForm> = Type::new(Path::default(), vec![], tuple, vec![]);
+    /// let mut ids = Vec::new();
+    /// ty.visit_type_ids(|id| ids.push(id));
+    /// assert_eq!(ids, [1, 2]);
+    /// ```
+    pub fn visit_type_ids<F>(&self, mut f: F)
+    where
+        F: FnMut(u32),
+    {
+        for param in self.type_params.iter() {
+            param.visit_type_ids(&mut f);
+        }
+        self.type_def.visit_type_ids(f);
+    }
+}
+
 impl<F> Type<F>
 where
     F: Form,
@@ -222,6 +269,26 @@ impl TypeParameter<PortableForm> {
     ) -> Self {
         Self { name, ty }
     }
+
+    /// Replaces the type id of the type parameter, if any, with the id returned by `f`.
+    pub fn map_type_ids<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        if let Some(ty) = &mut self.ty {
+            *ty = f(ty.id).into();
+        }
+    }
+
+    /// Calls `f` with the type id of the type parameter, if any.
+    pub fn visit_type_ids<F>(&self, mut f: F)
+    where
+        F: FnMut(u32),
+    {
+        if let Some(ty) = &self.ty {
+            f(ty.id);
+        }
+    }
 }
 
 impl<T> TypeParameter<T>
@@ -337,6 +404,80 @@ impl IntoPortable for TypeDef {
     }
 }
 
+impl TypeDef<PortableForm> {
+    /// Replaces every type id referred to by the definition with the id returned by `f`, in
+    /// the order of the fields, elements or parameters referring to them.
+    pub fn map_type_ids<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        match self {
+            TypeDef::Composite(composite) => {
+                for field in composite.fields.iter_mut() {
+                    field.map_type_ids(&mut f);
+                }
+            }
+            TypeDef::Variant(variant) => {
+                for variant in variant.variants.iter_mut() {
+                    variant.map_type_ids(&mut f);
+                }
+            }
+            TypeDef::Sequence(sequence) => {
+                sequence.type_param = f(sequence.type_param.id).into();
+            }
+            TypeDef::Array(array) => {
+                array.type_param = f(array.type_param.id).into();
+            }
+            TypeDef::Tuple(tuple) => {
+                for ty in tuple.fields.iter_mut() {
+                    *ty = f(ty.id).into();
+                }
+            }
+            TypeDef::Primitive(_) => (),
+            TypeDef::Compact(compact) => {
+                compact.type_param = f(compact.type_param.id).into();
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                bit_sequence.bit_store_type = f(bit_sequence.bit_store_type.id).into();
+                bit_sequence.bit_order_type = f(bit_sequence.bit_order_type.id).into();
+            }
+        }
+    }
+
+    /// Calls `f` with every type id referred to by the definition, in the order
+    /// [`map_type_ids`](Self::map_type_ids) replaces them.
+    pub fn visit_type_ids<F>(&self, mut f: F)
+    where
+        F: FnMut(u32),
+    {
+        match self {
+            TypeDef::Composite(composite) => {
+                for field in composite.fields.iter() {
+                    field.visit_type_ids(&mut f);
+                }
+            }
+            TypeDef::Variant(variant) => {
+                for variant in variant.variants.iter() {
+                    variant.visit_type_ids(&mut f);
+                }
+            }
+            TypeDef::Sequence(sequence) => f(sequence.type_param.id),
+            TypeDef::Array(array) => f(array.type_param.id),
+            TypeDef::Tuple(tuple) => {
+                for ty in tuple.fields.iter() {
+                    f(ty.id);
+                }
+            }
+            TypeDef::Primitive(_) => (),
+            TypeDef::Compact(compact) => f(compact.type_param.id),
+            TypeDef::BitSequence(bit_sequence) => {
+                f(bit_sequence.bit_store_type.id);
+                f(bit_sequence.bit_order_type.id);
+            }
+        }
+    }
+}
+
 /// A primitive Rust type.
 ///
 /// # Note
@@ -183,6 +183,28 @@ impl IntoPortable for Variant {
     }
 }
 
+impl Variant<PortableForm> {
+    /// Replaces the type ids of the fields of the variant with the ids returned by `f`.
+    pub fn map_type_ids<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        for field in self.fields.iter_mut() {
+            field.map_type_ids(&mut f);
+        }
+    }
+
+    /// Calls `f` with the type ids of the fields of the variant.
+    pub fn visit_type_ids<F>(&self, mut f: F)
+    where
+        F: FnMut(u32),
+    {
+        for field in self.fields.iter() {
+            field.visit_type_ids(&mut f);
+        }
+    }
+}
+
 impl<T> Variant<T>
 where
     T: Form,
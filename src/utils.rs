@@ -12,6 +12,89 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::prelude::vec::Vec;
+
+use crate::{form::PortableForm, Type, TypeDef, TypeDefPrimitive};
+
+/// Returns the number of bytes of a bit sequence store type, `None` if the type is not one of
+/// `u8`, `u16`, `u32` or `u64`.
+pub fn bit_store_bytes(ty: &Type<PortableForm>) -> Option<usize> {
+    match ty.type_def {
+        TypeDef::Primitive(TypeDefPrimitive::U8) => Some(1),
+        TypeDef::Primitive(TypeDefPrimitive::U16) => Some(2),
+        TypeDef::Primitive(TypeDefPrimitive::U32) => Some(4),
+        TypeDef::Primitive(TypeDefPrimitive::U64) => Some(8),
+        _ => None,
+    }
+}
+
+/// Returns `true` if a bit sequence order type is `Lsb0`, `false` if it is `Msb0` and `None`
+/// for any other type.
+pub fn bit_order_is_lsb0(ty: &Type<PortableForm>) -> Option<bool> {
+    match ty.path.ident().as_ref().map(AsRef::as_ref) {
+        Some("Lsb0") => Some(true),
+        Some("Msb0") => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the Rust name of a primitive type.
+pub fn primitive_kind(primitive: &TypeDefPrimitive) -> &'static str {
+    match primitive {
+        TypeDefPrimitive::Bool => "bool",
+        TypeDefPrimitive::Char => "char",
+        TypeDefPrimitive::Str => "str",
+        TypeDefPrimitive::U8 => "u8",
+        TypeDefPrimitive::U16 => "u16",
+        TypeDefPrimitive::U32 => "u32",
+        TypeDefPrimitive::U64 => "u64",
+        TypeDefPrimitive::U128 => "u128",
+        TypeDefPrimitive::U256 => "u256",
+        TypeDefPrimitive::I8 => "i8",
+        TypeDefPrimitive::I16 => "i16",
+        TypeDefPrimitive::I32 => "i32",
+        TypeDefPrimitive::I64 => "i64",
+        TypeDefPrimitive::I128 => "i128",
+        TypeDefPrimitive::I256 => "i256",
+    }
+}
+
+/// Returns the encoded size of a primitive, `None` for `str`, which has no fixed size.
+pub fn primitive_size(primitive: &TypeDefPrimitive) -> Option<usize> {
+    Some(match primitive {
+        TypeDefPrimitive::Bool | TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => 1,
+        TypeDefPrimitive::Char => 4,
+        TypeDefPrimitive::Str => return None,
+        TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => 2,
+        TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => 4,
+        TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => 8,
+        TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => 16,
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => 32,
+    })
+}
+
+/// Returns a short description of the kind of a type definition.
+pub fn type_def_kind(type_def: &TypeDef<PortableForm>) -> &'static str {
+    match type_def {
+        TypeDef::Composite(_) => "composite",
+        TypeDef::Variant(_) => "variant",
+        TypeDef::Sequence(_) => "sequence",
+        TypeDef::Array(_) => "array",
+        TypeDef::Tuple(_) => "tuple",
+        TypeDef::Primitive(primitive) => primitive_kind(primitive),
+        TypeDef::Compact(_) => "compact",
+        TypeDef::BitSequence(_) => "bit sequence",
+    }
+}
+
+/// Returns the ids of the types referred to by a type, including its type parameters, in
+/// declaration order.
+pub fn type_refs(ty: &Type<PortableForm>) -> Vec<u32> {
+    let mut refs = Vec::new();
+    ty.visit_type_ids(|id| refs.push(id));
+    refs
+}
+
 /// Returns `true` if the given string is a proper Rust identifier.
 pub fn is_rust_identifier(s: &str) -> bool {
     // Only ascii encoding is allowed.
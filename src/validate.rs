@@ -0,0 +1,373 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the well-formedness of a [`PortableRegistry`].
+//!
+//! Registries built from [`TypeInfo`](crate::TypeInfo) implementations are well-formed, but
+//! registries built by hand, e.g. with a [`PortableRegistryBuilder`](crate::PortableRegistryBuilder),
+//! or decoded from untrusted bytes may not be.
+
+use crate::prelude::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    form::PortableForm,
+    utils::{bit_store_bytes, is_rust_identifier, type_refs},
+    Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive,
+};
+
+impl PortableRegistry {
+    /// Checks that the registry is well-formed, returning all the problems found otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use scale_info::{PortableRegistry, PortableType, Type, TypeDefSequence};
+    /// # use scale_info::validate::{ValidationError, ValidationErrorKind};
+    /// let registry = PortableRegistry {
+    ///     types: vec![PortableType::new(0, TypeDefSequence::new(1.into()).into())],
+    /// };
+    /// assert_eq!(
+    ///     registry.validate(),
+    ///     Err(vec![ValidationError {
+    ///         type_id: 0,
+    ///         kind: ValidationErrorKind::DanglingTypeId(1),
+    ///     }])
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator {
+            registry: self,
+            errors: Vec::new(),
+        };
+        for (position, ty) in self.types.iter().enumerate() {
+            validator.validate_type(position as u32, ty.id, &ty.ty);
+        }
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+}
+
+/// A problem making a registry ill-formed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The id of the type with the problem, i.e. its position in the registry.
+    pub type_id: u32,
+    /// The kind of problem.
+    pub kind: ValidationErrorKind,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "type with id {}: {}", self.type_id, self.kind)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+/// The kind of problem making a registry ill-formed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// The [`PortableType::id`](crate::PortableType::id) differs from the position of the type.
+    IdMismatch(u32),
+    /// The type refers to a type id which is not in the registry.
+    DanglingTypeId(u32),
+    /// Several variants share an index.
+    DuplicateVariantIndex(u8),
+    /// Several variants share a name.
+    DuplicateVariantName(String),
+    /// Some fields are named and others are not, within the type or the given variant.
+    MixedFieldNames {
+        /// The variant with the fields, if the type is a variant type.
+        variant: Option<String>,
+    },
+    /// A segment of the path of the type is not a Rust identifier.
+    InvalidPathSegment(String),
+    /// The store type of a bit sequence is not one of `u8`, `u16`, `u32` or `u64`.
+    InvalidBitStoreType(u32),
+    /// A compact type wraps a type which can't be compact encoded.
+    NonCompactableType(u32),
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IdMismatch(id) => write!(f, "declared with id {id}"),
+            Self::DanglingTypeId(id) => write!(f, "refers to missing type with id {id}"),
+            Self::DuplicateVariantIndex(index) => {
+                write!(f, "several variants have index {index}")
+            }
+            Self::DuplicateVariantName(name) => {
+                write!(f, "several variants are named `{name}`")
+            }
+            Self::MixedFieldNames { variant: None } => {
+                write!(f, "mixes named and unnamed fields")
+            }
+            Self::MixedFieldNames {
+                variant: Some(variant),
+            } => write!(f, "variant `{variant}` mixes named and unnamed fields"),
+            Self::InvalidPathSegment(segment) => {
+                write!(f, "path segment `{segment}` is not a Rust identifier")
+            }
+            Self::InvalidBitStoreType(id) => {
+                write!(
+                    f,
+                    "bit store type with id {id} is not an unsigned primitive"
+                )
+            }
+            Self::NonCompactableType(id) => {
+                write!(f, "type with id {id} can not be compact encoded")
+            }
+        }
+    }
+}
+
+struct Validator<'a> {
+    registry: &'a PortableRegistry,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn validate_type(&mut self, type_id: u32, declared_id: u32, ty: &Type<PortableForm>) {
+        let mut errors = Vec::new();
+
+        if declared_id != type_id {
+            errors.push(ValidationErrorKind::IdMismatch(declared_id));
+        }
+        let mut refs = type_refs(ty);
+        refs.sort_unstable();
+        refs.dedup();
+        for id in refs {
+            if self.registry.resolve(id).is_none() {
+                errors.push(ValidationErrorKind::DanglingTypeId(id));
+            }
+        }
+        for segment in &ty.path.segments {
+            if !is_rust_identifier(segment.as_ref()) {
+                errors.push(ValidationErrorKind::InvalidPathSegment(segment.to_string()));
+            }
+        }
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) if has_mixed_names(&composite.fields) => {
+                errors.push(ValidationErrorKind::MixedFieldNames { variant: None });
+            }
+            TypeDef::Variant(variant) => {
+                let mut indices = BTreeSet::new();
+                let mut names = BTreeSet::new();
+                for variant in &variant.variants {
+                    let name: &str = variant.name.as_ref();
+                    if !indices.insert(variant.index) {
+                        errors.push(ValidationErrorKind::DuplicateVariantIndex(variant.index));
+                    }
+                    if !names.insert(name) {
+                        errors.push(ValidationErrorKind::DuplicateVariantName(name.to_string()));
+                    }
+                    if has_mixed_names(&variant.fields) {
+                        errors.push(ValidationErrorKind::MixedFieldNames {
+                            variant: Some(name.to_string()),
+                        });
+                    }
+                }
+            }
+            TypeDef::Compact(compact) => {
+                let id = compact.type_param.id;
+                if self.registry.resolve(id).is_some()
+                    && !self.is_compactable(id, &mut BTreeSet::new())
+                {
+                    errors.push(ValidationErrorKind::NonCompactableType(id));
+                }
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                let id = bit_sequence.bit_store_type.id;
+                // Missing store types are reported as dangling ids.
+                let store = self.registry.resolve(id);
+                if store.map_or(false, |store| bit_store_bytes(store).is_none()) {
+                    errors.push(ValidationErrorKind::InvalidBitStoreType(id));
+                }
+            }
+            _ => {}
+        }
+        self.errors.extend(
+            errors
+                .into_iter()
+                .map(|kind| ValidationError { type_id, kind }),
+        );
+    }
+
+    /// Returns `true` if the type is an unsigned integer, or a composite or tuple with at most
+    /// one field of a compactable type, as supported by `Compact`.
+    fn is_compactable(&self, id: u32, visited: &mut BTreeSet<u32>) -> bool {
+        // A type wrapping itself has no integer to compact.
+        if !visited.insert(id) {
+            return false;
+        }
+        let ty = match self.registry.resolve(id) {
+            Some(ty) => ty,
+            None => return false,
+        };
+        match &ty.type_def {
+            TypeDef::Primitive(primitive) => matches!(
+                primitive,
+                TypeDefPrimitive::U8
+                    | TypeDefPrimitive::U16
+                    | TypeDefPrimitive::U32
+                    | TypeDefPrimitive::U64
+                    | TypeDefPrimitive::U128
+            ),
+            TypeDef::Composite(composite) => match &composite.fields[..] {
+                [] => true,
+                [field] => self.is_compactable(field.ty.id, visited),
+                _ => false,
+            },
+            TypeDef::Tuple(tuple) => match &tuple.fields[..] {
+                [] => true,
+                [field] => self.is_compactable(field.id, visited),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+fn has_mixed_names(fields: &[Field<PortableForm>]) -> bool {
+    let named = fields.iter().filter(|f| f.name.is_some()).count();
+    named != 0 && named != fields.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::vec, *};
+    use scale::Compact;
+    use scale_info_derive::TypeInfo;
+
+    fn registry_with<T: TypeInfo + 'static>() -> PortableRegistry {
+        let mut registry = Registry::new();
+        registry.register_type(&MetaType::new::<T>());
+        registry.into()
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Wrapper(u64);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Call {
+        Transfer {
+            to: [u8; 32],
+            #[codec(compact)]
+            amount: u128,
+        },
+        Batch(Vec<Call>),
+        Remark(String),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Root {
+        call: Call,
+        wrapped: Compact<Wrapper>,
+        unit: Compact<()>,
+    }
+
+    #[test]
+    fn derived_registries_are_valid() {
+        assert_eq!(registry_with::<Root>().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_all_errors() {
+        let mut registry = PortableRegistryBuilder::new();
+        let primitive = |primitive| Type::new(Path::default(), vec![], primitive, vec![]);
+        let u8_id = registry.register_type(primitive(TypeDefPrimitive::U8));
+        let bool_id = registry.register_type(primitive(TypeDefPrimitive::Bool));
+        let variant = |name: &str, index, fields| Variant {
+            name: name.into(),
+            fields,
+            index,
+            docs: vec![],
+        };
+        let variant_id = registry.register_type(Type::new(
+            Path::from_segments_unchecked(["my-crate".into(), "Call".into()]),
+            vec![],
+            TypeDefVariant::new(vec![
+                variant("A", 0, vec![]),
+                variant("B", 0, vec![]),
+                variant(
+                    "A",
+                    1,
+                    vec![
+                        Field::new(Some("a".into()), u8_id.into(), None, vec![]),
+                        Field::new(None, u8_id.into(), None, vec![]),
+                    ],
+                ),
+            ]),
+            vec![],
+        ));
+        let mut registry = registry.finish();
+
+        let invalid: [TypeDef<PortableForm>; 3] = [
+            TypeDefCompact::new(bool_id.into()).into(),
+            TypeDefBitSequence::new_portable(bool_id.into(), u8_id.into()).into(),
+            TypeDefSequence::new(42.into()).into(),
+        ];
+        for type_def in invalid {
+            let id = registry.types.len() as u32;
+            let ty = Type::new(Path::default(), vec![], type_def, vec![]);
+            registry.types.push(PortableType::new(id, ty));
+        }
+        registry.types[0].id = 7;
+
+        let errors = registry.validate().unwrap_err();
+        let error = |type_id, kind| ValidationError { type_id, kind };
+        assert_eq!(
+            errors,
+            vec![
+                error(0, ValidationErrorKind::IdMismatch(7)),
+                error(
+                    variant_id,
+                    ValidationErrorKind::InvalidPathSegment("my-crate".into())
+                ),
+                error(variant_id, ValidationErrorKind::DuplicateVariantIndex(0)),
+                error(
+                    variant_id,
+                    ValidationErrorKind::DuplicateVariantName("A".into())
+                ),
+                error(
+                    variant_id,
+                    ValidationErrorKind::MixedFieldNames {
+                        variant: Some("A".into())
+                    }
+                ),
+                error(3, ValidationErrorKind::NonCompactableType(bool_id)),
+                error(4, ValidationErrorKind::InvalidBitStoreType(bool_id)),
+                error(5, ValidationErrorKind::DanglingTypeId(42)),
+            ]
+        );
+        assert_eq!(
+            errors[4].to_string(),
+            "type with id 2: variant `A` mixes named and unnamed fields"
+        );
+    }
+}
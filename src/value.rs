@@ -0,0 +1,240 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A dynamic representation of SCALE encoded values.
+//!
+//! A [`Value`] mirrors the shape of a [`TypeDef`](crate::TypeDef), so that values can be
+//! decoded from and encoded to SCALE bytes given only a
+//! [`PortableRegistry`](crate::PortableRegistry) and a type id, without any knowledge of the
+//! concrete Rust types involved.
+
+use crate::prelude::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A dynamic value, shaped after the [`TypeDef`](crate::TypeDef) which describes it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// A composite value (e.g. a struct or a tuple struct).
+    Composite(Composite),
+    /// A variant value (e.g. an enum variant).
+    Variant(VariantValue),
+    /// A sequence of values with a runtime known length.
+    Sequence(Vec<Value>),
+    /// An array of values with a compile-time known length.
+    Array(Vec<Value>),
+    /// A tuple of values.
+    Tuple(Vec<Value>),
+    /// A primitive value.
+    Primitive(Primitive),
+    /// A value using the [`Compact`](scale::Compact) encoding.
+    Compact(Box<Value>),
+    /// A sequence of bits.
+    BitSequence(Vec<bool>),
+}
+
+impl Value {
+    /// Creates a composite value with named fields.
+    pub fn named_composite<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (S, Value)>,
+        S: Into<String>,
+    {
+        Value::Composite(Composite::Named(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        ))
+    }
+
+    /// Creates a composite value with unnamed fields.
+    pub fn unnamed_composite<I>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        Value::Composite(Composite::Unnamed(fields.into_iter().collect()))
+    }
+
+    /// Creates a variant value with the given name and fields.
+    ///
+    /// The variant index is set to `0`. When encoding, variants are looked up by name.
+    pub fn variant<S: Into<String>>(name: S, fields: Composite) -> Self {
+        Value::Variant(VariantValue {
+            name: name.into(),
+            index: 0,
+            fields,
+        })
+    }
+
+    /// Creates a `bool` value.
+    pub fn bool(b: bool) -> Self {
+        Value::Primitive(Primitive::Bool(b))
+    }
+
+    /// Creates a `str` value.
+    pub fn string<S: Into<String>>(s: S) -> Self {
+        Value::Primitive(Primitive::Str(s.into()))
+    }
+
+    /// Creates a compact value wrapping the given value.
+    pub fn compact(value: Value) -> Self {
+        Value::Compact(Box::new(value))
+    }
+}
+
+impl From<Primitive> for Value {
+    fn from(primitive: Primitive) -> Self {
+        Value::Primitive(primitive)
+    }
+}
+
+/// The fields of a composite value or a variant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Composite {
+    /// Named fields, in declaration order.
+    Named(Vec<(String, Value)>),
+    /// Unnamed fields, in declaration order.
+    Unnamed(Vec<Value>),
+}
+
+impl Composite {
+    /// Creates an empty set of fields, e.g. for a unit struct or variant.
+    pub fn unit() -> Self {
+        Composite::Unnamed(Vec::new())
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        match self {
+            Composite::Named(fields) => fields.len(),
+            Composite::Unnamed(fields) => fields.len(),
+        }
+    }
+
+    /// Returns `true` if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the field values, ignoring any names.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        let (named, unnamed): (&[(String, Value)], &[Value]) = match self {
+            Composite::Named(fields) => (fields, &[]),
+            Composite::Unnamed(fields) => (&[], fields),
+        };
+        named.iter().map(|(_, value)| value).chain(unnamed)
+    }
+}
+
+/// A variant value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantValue {
+    /// The name of the variant.
+    pub name: String,
+    /// The index of the variant, as encoded.
+    pub index: u8,
+    /// The fields of the variant.
+    pub fields: Composite,
+}
+
+/// A primitive value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    /// A `bool`.
+    Bool(bool),
+    /// A `char`.
+    Char(char),
+    /// A `str`.
+    Str(String),
+    /// A `u8`.
+    U8(u8),
+    /// A `u16`.
+    U16(u16),
+    /// A `u32`.
+    U32(u32),
+    /// A `u64`.
+    U64(u64),
+    /// A `u128`.
+    U128(u128),
+    /// A 256 bits unsigned int, as little endian bytes.
+    U256([u8; 32]),
+    /// An `i8`.
+    I8(i8),
+    /// An `i16`.
+    I16(i16),
+    /// An `i32`.
+    I32(i32),
+    /// An `i64`.
+    I64(i64),
+    /// An `i128`.
+    I128(i128),
+    /// A 256 bits signed int, as little endian bytes.
+    I256([u8; 32]),
+}
+
+impl From<bool> for Primitive {
+    fn from(b: bool) -> Self {
+        Primitive::Bool(b)
+    }
+}
+
+impl From<char> for Primitive {
+    fn from(c: char) -> Self {
+        Primitive::Char(c)
+    }
+}
+
+impl From<&str> for Primitive {
+    fn from(s: &str) -> Self {
+        Primitive::Str(s.to_string())
+    }
+}
+
+impl From<String> for Primitive {
+    fn from(s: String) -> Self {
+        Primitive::Str(s)
+    }
+}
+
+macro_rules! impl_from_int_for_primitive {
+    ( $( $t:ty => $variant:ident, )* ) => { $(
+        impl From<$t> for Primitive {
+            fn from(x: $t) -> Self {
+                Primitive::$variant(x)
+            }
+        }
+
+        impl From<$t> for Value {
+            fn from(x: $t) -> Self {
+                Value::Primitive(Primitive::$variant(x))
+            }
+        }
+    )* }
+}
+
+impl_from_int_for_primitive!(
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+);
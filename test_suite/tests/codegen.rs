@@ -0,0 +1,308 @@
+// Copyright 2019-2022 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(dead_code)]
+
+use info::{self as scale_info};
+use pretty_assertions::assert_eq;
+use scale::{Decode, Encode};
+use scale_info::{
+    codegen::{CodegenError, RustGenerator, TypeScriptGenerator},
+    compat::check_compatibility,
+    strip::StripOptions,
+    MetaType, Path, PortableRegistry, Registry, TypeDef, TypeDefSequence, TypeInfo,
+};
+
+pub mod source {
+    use super::*;
+    use std::{collections::BTreeMap, marker::PhantomData};
+
+    /// A transfer of funds.
+    #[derive(Encode, Decode, TypeInfo)]
+    #[scale_info(crate = info)]
+    pub struct Transfer<Balance: scale::HasCompact> {
+        /// The recipient.
+        pub to: [u8; 32],
+        #[codec(compact)]
+        pub amount: Balance,
+        pub memo: Option<Vec<u8>>,
+    }
+
+    #[derive(Encode, Decode, TypeInfo)]
+    #[scale_info(crate = info)]
+    pub enum Call<Balance: scale::HasCompact> {
+        #[codec(index = 7)]
+        Transfer(Transfer<Balance>),
+        Remark {
+            data: String,
+        },
+        Batch(Vec<Call<Balance>>),
+        Noop,
+    }
+
+    pub mod nested {
+        use super::*;
+
+        #[derive(Encode, Decode, TypeInfo)]
+        #[scale_info(crate = info)]
+        pub struct Wrapper<T>(pub T, pub (u8, i16));
+
+        #[derive(Encode, Decode, TypeInfo)]
+        #[scale_info(crate = info, skip_type_params(M))]
+        pub struct Marker<M> {
+            pub x: u8,
+            #[codec(skip)]
+            pub marker: PhantomData<M>,
+        }
+    }
+
+    #[derive(Encode, Decode, TypeInfo)]
+    #[scale_info(crate = info, rename_all = "kebab-case")]
+    pub struct Renamed {
+        pub r#type: u8,
+        pub amount_paid: u128,
+        #[scale_info(rename = "self")]
+        pub this: bool,
+        #[scale_info(rename = "0x")]
+        pub hex: Vec<u8>,
+        pub kind: Kind,
+    }
+
+    #[derive(Encode, Decode, TypeInfo)]
+    #[scale_info(crate = info, rename_all = "snake_case")]
+    pub enum Kind {
+        Match,
+        TransferAll,
+        #[scale_info(rename = "transfer-all")]
+        TransferAllLegacy,
+    }
+
+    pub mod shadowed {
+        use super::*;
+
+        pub mod b {
+            use super::*;
+
+            #[derive(Encode, Decode, TypeInfo)]
+            #[scale_info(crate = info)]
+            pub struct Foo(pub u8);
+        }
+
+        pub mod x {
+            pub mod b {
+                use super::super::*;
+
+                #[derive(Encode, Decode, TypeInfo)]
+                #[scale_info(crate = info)]
+                pub struct Inner {
+                    pub f: super::super::b::Foo,
+                }
+            }
+        }
+    }
+
+    #[derive(Encode, Decode, TypeInfo)]
+    #[scale_info(crate = info)]
+    pub struct Root {
+        pub call: Call<u128>,
+        pub wrapped: nested::Wrapper<bool>,
+        pub map: BTreeMap<u32, String>,
+        pub marker: nested::Marker<u8>,
+    }
+}
+
+mod generated {
+    include!("codegen/generated.rs");
+}
+
+#[allow(non_camel_case_types)]
+mod renamed {
+    include!("codegen/renamed.rs");
+}
+
+fn registry_with<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let mut registry = Registry::new();
+    let id = registry.register_type(&MetaType::new::<T>()).id;
+    (id, registry.into())
+}
+
+/// The registry of the golden files, without the docs captured with the `docs` feature.
+fn golden_registry() -> PortableRegistry {
+    let (_, mut registry) = registry_with::<source::Root>();
+    registry.strip(&StripOptions {
+        docs: true,
+        ..Default::default()
+    });
+    registry
+}
+
+fn generator() -> RustGenerator {
+    RustGenerator::new()
+        .codec_crate("::scale")
+        .scale_info_crate("::info")
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    assert_eq!(
+        generator().generate(&golden_registry()).unwrap(),
+        include_str!("codegen/generated.rs")
+    );
+}
+
+#[test]
+fn generated_typescript_is_up_to_date() {
+    let generator = TypeScriptGenerator::new().substitute("BTreeMap", "Map");
+    assert_eq!(
+        generator.generate(&golden_registry()).unwrap(),
+        include_str!("codegen/generated.ts")
+    );
+}
+
+#[test]
+fn generated_types_are_compatible() {
+    let (source_id, source) = registry_with::<source::Root>();
+    let (generated_id, generated) = registry_with::<generated::codegen::source::Root>();
+    assert_eq!(
+        check_compatibility(&source, source_id, &generated, generated_id),
+        vec![]
+    );
+
+    let root = source::Root {
+        call: source::Call::Batch(vec![
+            source::Call::Transfer(source::Transfer {
+                to: [7; 32],
+                amount: 1_000_000,
+                memo: Some(b"rent".to_vec()),
+            }),
+            source::Call::Remark {
+                data: "hello".into(),
+            },
+            source::Call::Noop,
+        ]),
+        wrapped: source::nested::Wrapper(true, (1, -2)),
+        map: [(1, "one".into())].into_iter().collect(),
+        marker: source::nested::Marker {
+            x: 5,
+            marker: Default::default(),
+        },
+    };
+    let encoded = root.encode();
+    let decoded = generated::codegen::source::Root::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(decoded.encode(), encoded);
+}
+
+#[test]
+fn generated_names_are_identifiers() {
+    let (source_id, source) = registry_with::<source::Renamed>();
+    assert_eq!(
+        generator().generate(&source).unwrap(),
+        include_str!("codegen/renamed.rs")
+    );
+
+    let (renamed_id, renamed) = registry_with::<renamed::codegen::source::Renamed>();
+    assert_eq!(
+        check_compatibility(&source, source_id, &renamed, renamed_id),
+        vec![]
+    );
+    let names = |registry: &PortableRegistry| -> Vec<Vec<String>> {
+        registry
+            .types
+            .iter()
+            .map(|ty| match &ty.ty.type_def {
+                TypeDef::Composite(composite) => composite
+                    .fields
+                    .iter()
+                    .filter_map(|f| f.name.clone())
+                    .collect(),
+                TypeDef::Variant(variant) => {
+                    variant.variants.iter().map(|v| v.name.clone()).collect()
+                }
+                _ => vec![],
+            })
+            .collect()
+    };
+    assert_eq!(names(&source), names(&renamed));
+}
+
+#[test]
+fn generated_typescript_names_are_quoted() {
+    let (_, registry) = registry_with::<source::Renamed>();
+    assert_eq!(
+        TypeScriptGenerator::new().generate(&registry).unwrap(),
+        include_str!("codegen/renamed.ts")
+    );
+}
+
+#[test]
+fn generated_typescript_references_are_not_shadowed() {
+    let (_, registry) = registry_with::<source::shadowed::x::b::Inner>();
+    assert_eq!(
+        TypeScriptGenerator::new().generate(&registry).unwrap(),
+        include_str!("codegen/shadowed.ts")
+    );
+}
+
+#[test]
+fn generate_fails_for_types_without_a_rendering() {
+    let (_, registry) = registry_with::<source::Renamed>();
+    let kind_id = registry
+        .types
+        .iter()
+        .find(|ty| ty.ty.path.ident().as_deref() == Some("Kind"))
+        .unwrap()
+        .id;
+
+    let mut unnamed = registry.clone();
+    unnamed.types[kind_id as usize].ty.path = Path::default();
+    assert_eq!(
+        generator().generate(&unnamed),
+        Err(CodegenError::UnnamedVariant(kind_id))
+    );
+
+    let mut missing = registry.clone();
+    missing.types.retain(|ty| ty.id != kind_id);
+    assert_eq!(
+        generator().generate(&missing),
+        Err(CodegenError::TypeNotFound(kind_id))
+    );
+
+    // A sequence of itself has no path to refer to it by.
+    let mut recursive = registry;
+    let bytes_id = recursive
+        .types
+        .iter()
+        .find(|ty| matches!(ty.ty.type_def, TypeDef::Sequence(_)))
+        .unwrap()
+        .id;
+    recursive.types[bytes_id as usize].ty.type_def = TypeDefSequence::new(bytes_id.into()).into();
+    assert_eq!(recursive.validate(), Ok(()));
+    assert_eq!(
+        generator().generate(&recursive),
+        Err(CodegenError::RecursiveType(bytes_id))
+    );
+    assert_eq!(
+        TypeScriptGenerator::new().generate(&recursive),
+        Err(CodegenError::RecursiveType(bytes_id))
+    );
+}
+
+#[test]
+fn generated_typescript_docs_are_escaped() {
+    let (id, mut registry) = registry_with::<source::Renamed>();
+    registry.types[id as usize].ty.docs = vec!["Matches `/*/` and `*/`.".into()];
+    let generated = TypeScriptGenerator::new().generate(&registry).unwrap();
+    assert!(generated.contains(" * Matches `/*\\/` and `*\\/`.\n"));
+    assert_eq!(generated.matches("*/").count(), 1);
+}
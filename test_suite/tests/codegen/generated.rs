@@ -0,0 +1,62 @@
+pub mod codegen {
+    pub mod source {
+        #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+        #[codec(crate = ::scale)]
+        #[scale_info(crate = ::info)]
+        pub struct Root {
+            pub call: Call<u128>,
+            pub wrapped: nested::Wrapper<bool>,
+            pub map: ::std::collections::BTreeMap<u32, ::std::string::String>,
+            pub marker: nested::Marker<()>,
+        }
+
+        #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+        #[codec(crate = ::scale)]
+        #[scale_info(crate = ::info)]
+        pub enum Call<Balance> {
+            #[codec(index = 7)]
+            Transfer(
+                Transfer<Balance>,
+            ),
+            #[codec(index = 1)]
+            Remark {
+                data: ::std::string::String,
+            },
+            #[codec(index = 2)]
+            Batch(
+                ::std::vec::Vec<Call<Balance>>,
+            ),
+            #[codec(index = 3)]
+            Noop,
+        }
+
+        #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+        #[codec(crate = ::scale)]
+        #[scale_info(crate = ::info)]
+        pub struct Transfer<Balance> {
+            pub to: [u8; 32],
+            #[codec(compact)]
+            pub amount: Balance,
+            pub memo: ::core::option::Option<::std::vec::Vec<u8>>,
+        }
+
+        pub mod nested {
+            #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+            #[codec(crate = ::scale)]
+            #[scale_info(crate = ::info)]
+            pub struct Wrapper<T>(
+                pub T,
+                pub (u8, i16),
+            );
+
+            #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+            #[codec(crate = ::scale)]
+            #[scale_info(crate = ::info)]
+            pub struct Marker<M> {
+                pub x: u8,
+                #[codec(skip)]
+                pub __phantom: ::core::marker::PhantomData<(M,)>,
+            }
+        }
+    }
+}
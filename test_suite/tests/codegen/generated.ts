@@ -0,0 +1,34 @@
+export type Option<T> =
+    | { type: "None" }
+    | { type: "Some"; value: T };
+
+export namespace codegen {
+    export namespace source {
+        export interface Root {
+            call: Call<bigint>;
+            wrapped: nested.Wrapper<boolean>;
+            map: Map<number, string>;
+            marker: nested.Marker<unknown>;
+        }
+
+        export type Call<Balance> =
+            | { type: "Transfer"; value: Transfer<Balance> }
+            | { type: "Remark"; value: { data: string } }
+            | { type: "Batch"; value: Call<Balance>[] }
+            | { type: "Noop" };
+
+        export interface Transfer<Balance> {
+            to: number[];
+            amount: Balance;
+            memo: Option<number[]>;
+        }
+
+        export namespace nested {
+            export type Wrapper<T> = [T, [number, number]];
+
+            export interface Marker<M> {
+                x: number;
+            }
+        }
+    }
+}
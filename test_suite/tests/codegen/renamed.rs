@@ -0,0 +1,32 @@
+pub mod codegen {
+    pub mod source {
+        #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+        #[codec(crate = ::scale)]
+        #[scale_info(crate = ::info)]
+        pub struct Renamed {
+            #[scale_info(rename = "type")]
+            pub r#type: u8,
+            #[scale_info(rename = "amount-paid")]
+            pub amount_paid: u128,
+            #[scale_info(rename = "self")]
+            pub self_: bool,
+            #[scale_info(rename = "0x")]
+            pub _0x: ::std::vec::Vec<u8>,
+            pub kind: Kind,
+        }
+
+        #[derive(::scale::Encode, ::scale::Decode, ::info::TypeInfo)]
+        #[codec(crate = ::scale)]
+        #[scale_info(crate = ::info)]
+        pub enum Kind {
+            #[codec(index = 0)]
+            #[scale_info(rename = "match")]
+            r#match,
+            #[codec(index = 1)]
+            transfer_all,
+            #[codec(index = 2)]
+            #[scale_info(rename = "transfer-all")]
+            transfer_all_1,
+        }
+    }
+}
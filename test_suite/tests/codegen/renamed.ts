@@ -0,0 +1,16 @@
+export namespace codegen {
+    export namespace source {
+        export interface Renamed {
+            type: number;
+            "amount-paid": bigint;
+            self: boolean;
+            "0x": number[];
+            kind: Kind;
+        }
+
+        export type Kind =
+            | { type: "match" }
+            | { type: "transfer_all" }
+            | { type: "transfer-all" };
+    }
+}
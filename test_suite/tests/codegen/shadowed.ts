@@ -0,0 +1,17 @@
+export namespace codegen {
+    export namespace source {
+        export namespace shadowed {
+            export namespace b {
+                export type Foo = number;
+            }
+
+            export namespace x {
+                export namespace b {
+                    export interface Inner {
+                        f: shadowed.b.Foo;
+                    }
+                }
+            }
+        }
+    }
+}
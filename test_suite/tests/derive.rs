@@ -450,6 +450,39 @@ fn scale_compact_types_work_in_enums() {
     assert_type!(MutilatedMultiAddress<u8, u16>, ty);
 }
 
+#[test]
+fn scale_encoded_as_types_work() {
+    #[allow(unused)]
+    #[derive(Encode, TypeInfo)]
+    struct S {
+        #[codec(encoded_as = "scale::Compact<u64>")]
+        a: u64,
+        b: bool,
+    }
+
+    let ty = Type::builder().path(Path::new("S", "derive")).composite(
+        Fields::named()
+            .field(|f| f.compact::<u64>().name("a").type_name("u64"))
+            .field(|f| f.ty::<bool>().name("b").type_name("bool")),
+    );
+    assert_type!(S, ty);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E<T> {
+        A(#[codec(encoded_as = "Vec<T>")] Box<[T]>),
+    }
+
+    let ty = Type::builder()
+        .path(Path::new("E", "derive"))
+        .type_params(named_type_params![(T, u8)])
+        .variant(Variants::new().variant("A", |v| {
+            v.index(0)
+                .fields(Fields::unnamed().field(|f| f.ty::<Vec<u8>>().type_name("Box<[T]>")))
+        }));
+    assert_type!(E<u8>, ty);
+}
+
 #[test]
 fn struct_fields_marked_scale_skip_are_skipped() {
     #[allow(unused)]
@@ -929,3 +962,224 @@ fn rename_field() {
 
     assert_type!(S, ty);
 }
+
+#[test]
+fn with_field_type_override() {
+    #[allow(unused)]
+    mod foreign {
+        pub struct Hash(pub [u8; 32]);
+
+        pub struct Opaque<T>(pub super::Vec<T>);
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S<T> {
+        #[scale_info(with = [u8; 32])]
+        hash: foreign::Hash,
+        #[scale_info(with = Vec<T>)]
+        items: foreign::Opaque<T>,
+        #[codec(compact)]
+        #[scale_info(with = u64)]
+        count: foreign::Opaque<u64>,
+    }
+
+    let ty = Type::builder()
+        .path(Path::new("S", "derive"))
+        .type_params(named_type_params![(T, bool)])
+        .composite(
+            Fields::named()
+                .field(|f| f.ty::<[u8; 32]>().name("hash").type_name("foreign::Hash"))
+                .field(|f| {
+                    f.ty::<Vec<bool>>()
+                        .name("items")
+                        .type_name("foreign::Opaque<T>")
+                })
+                .field(|f| {
+                    f.compact::<u64>()
+                        .name("count")
+                        .type_name("foreign::Opaque<u64>")
+                }),
+        );
+
+    assert_type!(S<bool>, ty);
+}
+
+#[test]
+fn combined_field_attributes() {
+    #[allow(unused)]
+    struct Opaque<T>(Vec<T>);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S<T> {
+        #[scale_info(with = Vec<T>, rename = "values")]
+        items: Opaque<T>,
+        #[scale_info(rename = "count")]
+        #[scale_info(with = u64)]
+        len: Opaque<u64>,
+    }
+
+    let ty = Type::builder()
+        .path(Path::new("S", "derive"))
+        .type_params(named_type_params![(T, bool)])
+        .composite(
+            Fields::named()
+                .field(|f| f.ty::<Vec<bool>>().name("values").type_name("Opaque<T>"))
+                .field(|f| f.ty::<u64>().name("count").type_name("Opaque<u64>")),
+        );
+
+    assert_type!(S<bool>, ty);
+}
+
+#[test]
+fn transparent_struct() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(transparent)]
+    struct Balance(u128);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(transparent)]
+    struct Wrapper<T, U> {
+        inner: Vec<T>,
+        #[codec(skip)]
+        marker: PhantomData<U>,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(transparent)]
+    struct CompactBalance(#[codec(compact)] u128);
+
+    assert_type!(Balance, u128::type_info());
+    assert_type!(Wrapper<u8, bool>, Vec::<u8>::type_info());
+    assert_type!(CompactBalance, scale::Compact::<u128>::type_info());
+    assert_ne!(MetaType::new::<Balance>(), MetaType::new::<u128>());
+}
+
+#[test]
+fn transparent_identity_struct() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(transparent(identity))]
+    struct Balance(u128);
+
+    assert_type!(Balance, u128::type_info());
+    assert_eq!(MetaType::new::<Balance>(), MetaType::new::<u128>());
+}
+
+#[test]
+fn rename_variants_and_type() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(rename = "Event")]
+    enum E {
+        #[scale_info(rename = "transfer")]
+        Transfer {
+            amount: u128,
+        },
+        Burn,
+    }
+
+    let ty = Type::builder().path(Path::new("Event", "derive")).variant(
+        Variants::new()
+            .variant("transfer", |v| {
+                v.index(0).fields(
+                    Fields::named().field(|f| f.ty::<u128>().name("amount").type_name("u128")),
+                )
+            })
+            .variant("Burn", |v| v.index(1)),
+    );
+
+    assert_type!(E, ty);
+}
+
+#[test]
+fn rename_all_fields_and_variants() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(rename_all = "camelCase")]
+    struct S {
+        amount_paid: u128,
+        r#type: u8,
+        #[scale_info(rename = "OTHER")]
+        other_field: bool,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    #[scale_info(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum E {
+        TransferExecuted { amount_paid: u128 },
+        Burned(u8),
+    }
+
+    let s = Type::builder().path(Path::new("S", "derive")).composite(
+        Fields::named()
+            .field(|f| f.ty::<u128>().name("amountPaid").type_name("u128"))
+            .field(|f| f.ty::<u8>().name("type").type_name("u8"))
+            .field(|f| f.ty::<bool>().name("OTHER").type_name("bool")),
+    );
+    let e = Type::builder().path(Path::new("E", "derive")).variant(
+        Variants::new()
+            .variant("TRANSFER_EXECUTED", |v| {
+                v.index(0).fields(
+                    Fields::named().field(|f| f.ty::<u128>().name("amount_paid").type_name("u128")),
+                )
+            })
+            .variant("BURNED", |v| {
+                v.index(1)
+                    .fields(Fields::unnamed().field(|f| f.ty::<u8>().type_name("u8")))
+            }),
+    );
+
+    assert_type!(S, s);
+    assert_type!(E, e);
+}
+
+#[test]
+fn scale_info_skip_fields_and_variants() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        a: u8,
+        #[scale_info(skip)]
+        internal: u16,
+        b: bool,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E {
+        #[scale_info(skip)]
+        Deprecated,
+        A(#[scale_info(skip)] u16, u8),
+        #[codec(skip)]
+        Skipped,
+        B,
+        #[codec(index = 5)]
+        #[scale_info(skip)]
+        Internal,
+        C,
+    }
+
+    let s = Type::builder().path(Path::new("S", "derive")).composite(
+        Fields::named()
+            .field(|f| f.ty::<u8>().name("a").type_name("u8"))
+            .field(|f| f.ty::<bool>().name("b").type_name("bool")),
+    );
+    let e = Type::builder().path(Path::new("E", "derive")).variant(
+        Variants::new()
+            .variant("A", |v| {
+                v.index(1)
+                    .fields(Fields::unnamed().field(|f| f.ty::<u8>().type_name("u8")))
+            })
+            .variant("B", |v| v.index(2))
+            .variant("C", |v| v.index(4)),
+    );
+
+    assert_type!(S, s);
+    assert_type!(E, e);
+}
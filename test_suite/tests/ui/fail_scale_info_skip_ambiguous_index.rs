@@ -0,0 +1,12 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+enum A {
+    #[scale_info(skip)]
+    Hidden,
+    #[codec(index = 0)]
+    B,
+}
+
+fn main() {}
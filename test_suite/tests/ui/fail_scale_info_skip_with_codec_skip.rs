@@ -0,0 +1,12 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+enum A {
+    #[codec(skip)]
+    #[scale_info(skip)]
+    Hidden,
+    B,
+}
+
+fn main() {}
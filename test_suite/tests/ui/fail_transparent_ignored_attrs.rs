@@ -0,0 +1,18 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+#[scale_info(transparent)]
+#[scale_info(rename_all = "snake_case")]
+struct A {
+    a: u8,
+}
+
+#[derive(TypeInfo)]
+#[scale_info(transparent)]
+struct B {
+    #[scale_info(rename = "b")]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+#[scale_info(transparent)]
+struct A {
+    a: u8,
+    b: u16,
+}
+
+fn main() {}
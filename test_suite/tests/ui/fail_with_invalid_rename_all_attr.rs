@@ -0,0 +1,10 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+#[scale_info(rename_all = "Title Case")]
+struct A {
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use info::{self as scale_info};
+use scale_info::TypeInfo;
+
+#[derive(TypeInfo)]
+struct InvalidKeywordInScaleInfoFieldAttr {
+    #[scale_info(wiht = u32)]
+    a: u8,
+}
+
+fn main() {}